@@ -1,20 +1,19 @@
 mod citp_tcp;
 
 use crate::citp_tcp::CaexState;
-use citp::protocol::{caex, pinf, ReadFromBytes, SizeBytes, Ucs2, WriteToBytes};
-use citp_tcp::CitpTcp;
+use citp::net::{announce::PeerAnnouncer, tcp::Session};
+use citp::protocol::{caex, pinf, Packet, PinfMessage, Ucs2, WriteToBytes};
 use socket2::{Domain, Protocol, SockAddr, Socket, Type};
 use std::{
     borrow::Cow,
     ffi::CString,
-    io::{self, Write},
+    io,
     mem::MaybeUninit,
-    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, TcpStream},
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
     str::FromStr,
+    time::Instant,
 };
 
-pub const CITP_HEADER_LEN: usize = 20;
-pub const CONTENT_TYPE_LEN: usize = 4;
 pub const NUM_LASERS: i32 = 1;
 
 #[derive(Debug)]
@@ -27,7 +26,7 @@ enum State {
 
 fn main() -> io::Result<()> {
     let mut state = State::Init;
-    let mut citp_tcp_stream: Option<CitpTcp> = None;
+    let mut citp_tcp_stream: Option<Session> = None;
     let mut buf: [MaybeUninit<u8>; 65535] = unsafe { MaybeUninit::uninit().assume_init() };
     let mut frame_num = 0;
     let source_key = rand::random::<u32>();
@@ -37,6 +36,9 @@ fn main() -> io::Result<()> {
     let multicast_address = Ipv4Addr::new(addr[0], addr[1], addr[2], addr[3]);
     let destination = SocketAddr::new(IpAddr::V4(multicast_address), port);
 
+    // Announces our peer location to the capture software
+    let mut announcer = PeerAnnouncer::new(peer_location())?;
+
     // Sending socket
     let send_socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
 
@@ -57,80 +59,48 @@ fn main() -> io::Result<()> {
                 Err(err) => eprintln!("join_multicast_v4 {:?}", err),
             },
             State::Connect => {
-                match recv_socket.recv_from(&mut buf) {
-                    Ok((len, ..)) => {
-                        // - Read the full base **Header** first.
-                        let data = to_data(&mut buf, len);
-                        let header = citp::protocol::Header::read_from_bytes(data)?;
-                        let header_size = header.size_bytes();
-
-                        match &header.content_type.to_le_bytes() {
-                            pinf::Header::CONTENT_TYPE => {
-                                if let pinf::PLoc::CONTENT_TYPE =
-                                    &layer_two_content_type(&data, header_size).to_le_bytes()
-                                {
-                                    let ploc = citp::protocol::pinf::PLoc::read_from_bytes(
-                                        &data[header_size + CONTENT_TYPE_LEN..],
-                                    )?;
-                                    let name = ploc.name.to_str().unwrap().to_owned();
-                                    let tcp_addr = format!(
-                                        "{}:{}",
-                                        extract_ip_address(&name),
-                                        ploc.listening_tcp_port
-                                    );
-
-                                    let stream = TcpStream::connect(tcp_addr)
-                                        .expect("Could not connect to server");
-                                    let mut citp_tcp = CitpTcp::new(stream)?;
-                                    let pnam_message = connect_to_capture();
-                                    pnam_message
-                                        .write_to_bytes(&mut citp_tcp.writer)
-                                        .expect("Failed to write to server");
-
-                                    // Tell TCP to send the buffered data on the wire
-                                    citp_tcp.writer.flush()?;
-                                    citp_tcp_stream = Some(citp_tcp);
-                                    state = State::Request;
-                                }
-                            }
-                            _ => eprintln!("Connect: Unrecognized UDP Header Content Type"),
-                        }
+                if let Ok((len, ..)) = recv_socket.recv_from(&mut buf) {
+                    let data = to_data(&mut buf, len);
+                    if let Packet::Pinf(PinfMessage::PLoc(msg)) = Packet::decode(data)? {
+                        let ploc = msg.message;
+                        let name = ploc.name.to_str().unwrap().to_owned();
+                        let tcp_addr =
+                            format!("{}:{}", extract_ip_address(&name), ploc.listening_tcp_port);
+
+                        let mut session =
+                            Session::connect(tcp_addr).expect("Could not connect to server");
+                        let pnam_message = connect_to_capture();
+                        session
+                            .send(&pnam_message)
+                            .expect("Failed to write to server");
+                        citp_tcp_stream = Some(session);
+                        state = State::Request;
                     }
-                    Err(_) => {}
                 }
             }
             State::Request => {
-                send_peer_location(&send_socket, destination);
+                announcer.poll(Instant::now())?;
                 if let Some(ref mut stream) = citp_tcp_stream {
-                    let caex_state = stream.read_message()?;
+                    let caex_state = citp_tcp::read_message(stream)?;
                     if let Some(CaexState::EnterShow) = caex_state {
                         let enter_show = enter_show("kortex-test-suite");
-                        enter_show
-                            .write_to_bytes(&mut stream.writer)
-                            .expect("Failed to write to server");
-                        stream.writer.flush()?;
+                        stream.send(&enter_show).expect("Failed to write to server");
                     }
                     if let Some(CaexState::GetLaserFeedList) = caex_state {
                         let feed_list = send_laser_feed_list(source_key);
-                        feed_list
-                            .write_to_bytes(&mut stream.writer)
-                            .expect("Failed to write to server");
-                        stream.writer.flush()?;
+                        stream.send(&feed_list).expect("Failed to write to server");
                     }
                     if let Some(CaexState::FixtureListRequest) = caex_state {
                         let fixture_list = new_fixture_list();
-                        fixture_list
-                            .write_to_bytes(&mut stream.writer)
+                        stream
+                            .send(&fixture_list)
                             .expect("Failed to write to server");
-                        stream.writer.flush()?;
                         state = State::Stream;
                     }
                 }
             }
             State::Stream => {
-                if frame_num % 100 == 0 {
-                    send_peer_location(&send_socket, destination);
-                }
+                announcer.poll(Instant::now())?;
 
                 for i in 0..NUM_LASERS {
                     let laser_frame = stream_laser_frame(source_key, frame_num, i as u8);
@@ -150,58 +120,14 @@ fn main() -> io::Result<()> {
     }
 }
 
-pub fn layer_two_content_type(data: &[u8], header_size: usize) -> u32 {
-    let slice = &data[header_size..header_size + CONTENT_TYPE_LEN];
-    u32::from_le_bytes([slice[0], slice[1], slice[2], slice[3]])
-}
-
-fn pinf_header(pinf_message_size: usize, content_type: u32) -> pinf::Header {
-    pinf::Header {
-        citp_header: citp::protocol::Header {
-            cookie: u32::from_le_bytes(*b"CITP"),
-            version_major: 1,
-            version_minor: 0,
-            kind: citp::protocol::Kind { request_index: 0 },
-            message_size: (CITP_HEADER_LEN + CONTENT_TYPE_LEN + pinf_message_size) as u32,
-            message_part_count: 1,
-            message_part: 0,
-            content_type: u32::from_le_bytes(*pinf::Header::CONTENT_TYPE),
-        },
-        content_type,
-    }
-}
-
-fn peer_location() -> pinf::Message<pinf::PLoc> {
+// The CITP/PINF/PLoc announced with no listening port.
+fn peer_location() -> pinf::PLoc {
     let error_msg = "CString::new failed";
-    let ploc = pinf::PLoc {
+    pinf::PLoc {
         listening_tcp_port: 0,
-        kind: CString::new("LightingConsole").expect(error_msg),
+        kind: pinf::PeerKind::LightingConsole,
         name: CString::new("Rusty Previz Tool").expect(error_msg),
         state: CString::new("Firing ze lasers").expect(error_msg),
-    };
-    pinf::Message {
-        pinf_header: pinf_header(
-            ploc.size_bytes(),
-            u32::from_le_bytes(*pinf::PLoc::CONTENT_TYPE),
-        ),
-        message: ploc,
-    }
-}
-
-// Regularly send a CITP/PINF/PLoc message with no listening port.
-fn send_peer_location(send_socket: &Socket, destination: SocketAddr) {
-    let ploc = peer_location();
-    let mut ploc_buf = [0u8; 65535];
-    match ploc.write_to_bytes(&mut ploc_buf[..]) {
-        Ok(_) => {
-            let len = ploc.pinf_header.citp_header.message_size as usize;
-            send_socket
-                .send_to(&ploc_buf[..len], &SockAddr::from(destination))
-                .expect("Can't send buffer over UDP Socket");
-        }
-        Err(_) => {
-            eprintln!("error writing ploc to bytes");
-        }
     }
 }
 
@@ -209,39 +135,14 @@ fn connect_to_capture() -> pinf::Message<pinf::PNam> {
     let pnam = pinf::PNam {
         name: CString::new("Rusty Laser Software").expect("CString::new failed"),
     };
-    pinf::Message {
-        pinf_header: pinf_header(
-            pnam.size_bytes(),
-            u32::from_le_bytes(*pinf::PNam::CONTENT_TYPE),
-        ),
-        message: pnam,
-    }
+    pinf::Message::new(pnam)
 }
 
 fn enter_show(project_name: &str) -> caex::Message<caex::EnterShow> {
     let enter_show = caex::EnterShow {
         name: citp::protocol::Ucs2::from_str(project_name).unwrap(),
     };
-    caex::Message {
-        caex_header: caex_header(enter_show.size_bytes(), caex::EnterShow::CONTENT_TYPE),
-        message: enter_show,
-    }
-}
-
-fn caex_header(caex_message_size: usize, content_type: u32) -> caex::Header {
-    caex::Header {
-        citp_header: citp::protocol::Header {
-            cookie: u32::from_le_bytes(*b"CITP"),
-            version_major: 1,
-            version_minor: 0,
-            kind: citp::protocol::Kind { request_index: 0 },
-            message_size: (CITP_HEADER_LEN + CONTENT_TYPE_LEN + caex_message_size) as u32,
-            message_part_count: 1,
-            message_part: 0,
-            content_type: u32::from_le_bytes(*b"CAEX"),
-        },
-        content_type,
-    }
+    caex::Message::new(enter_show)
 }
 
 fn send_laser_feed_list<'a>(source_key: u32) -> caex::Message<caex::LaserFeedList<'a>> {
@@ -252,14 +153,11 @@ fn send_laser_feed_list<'a>(source_key: u32) -> caex::Message<caex::LaserFeedLis
         test_list.push(ucs2);
     }
     let feed_list = caex::LaserFeedList {
-        source_key: source_key,
+        source_key,
         feed_count: test_list.len() as u8,
         feed_names: std::borrow::Cow::from(test_list),
     };
-    caex::Message {
-        caex_header: caex_header(feed_list.size_bytes(), caex::LaserFeedList::CONTENT_TYPE),
-        message: feed_list,
-    }
+    caex::Message::new(feed_list)
 }
 
 fn stream_laser_frame<'a>(
@@ -309,10 +207,7 @@ fn stream_laser_frame<'a>(
         point_count: points.len() as u16,
         points: std::borrow::Cow::from(points),
     };
-    caex::Message {
-        caex_header: caex_header(feed_frame.size_bytes(), caex::LaserFeedFrame::CONTENT_TYPE),
-        message: feed_frame,
-    }
+    caex::Message::new(feed_frame)
 }
 
 fn new_fixture_list<'a>() -> caex::Message<caex::FixtureList<'a>> {
@@ -321,13 +216,10 @@ fn new_fixture_list<'a>() -> caex::Message<caex::FixtureList<'a>> {
         fixture_count: 0,
         fixtures: Cow::Owned(vec![]),
     };
-    caex::Message {
-        caex_header: caex_header(fixture_list.size_bytes(), caex::FixtureList::CONTENT_TYPE),
-        message: fixture_list,
-    }
+    caex::Message::new(fixture_list)
 }
 
-fn extract_ip_address(s: &String) -> String {
+fn extract_ip_address(s: &str) -> String {
     let start_bytes = s.find("(").unwrap_or(0) + 1;
     let end_bytes = s.find(")").unwrap_or(s.len());
     s[start_bytes..end_bytes].to_string()
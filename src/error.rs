@@ -0,0 +1,219 @@
+use crate::protocol::Cookie;
+use std::{error, fmt, io};
+
+/// A specialized `Result` type for reading and writing CITP protocol types.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors that may occur while reading or writing CITP protocol types.
+#[derive(Debug)]
+pub enum Error {
+    /// An error produced by the underlying reader or writer.
+    Io(io::Error),
+    /// The base header cookie was not "CITP".
+    BadCookie { found: Cookie },
+    /// The base header carries a version that is not supported by this implementation.
+    UnsupportedVersion { major: u8, minor: u8 },
+    /// The message payload was shorter than its fields describe.
+    Truncated { expected: usize, actual: usize },
+    /// A message carries an MSEX version other than the one established for the session.
+    VersionMismatch {
+        expected_major: u8,
+        expected_minor: u8,
+        major: u8,
+        minor: u8,
+    },
+    /// The peers share no MSEX version through which they may communicate.
+    NoCommonVersion,
+    /// The content type of the base header does not identify a known layer.
+    UnknownLayer { layer: Cookie },
+    /// The content type of the layer header does not identify a known message.
+    UnknownMessage { layer: Cookie, cookie: Cookie },
+    /// A layer or message cookie does not match the type being read.
+    ContentTypeMismatch { expected: Cookie, found: Cookie },
+    /// The `message_size` of the base header does not match the size of the message that was read.
+    MessageSizeMismatch { message_size: usize, actual: usize },
+    /// The `message_size` of the base header exceeds the maximum size accepted by the receiver.
+    MessageTooLarge { message_size: usize, max: usize },
+    /// The `message_part` of the base header is not below its `message_part_count`.
+    InvalidMessagePart {
+        message_part: u16,
+        message_part_count: u16,
+    },
+    /// A field contained a value that does not match any variant of the named type.
+    InvalidDiscriminant { ty: &'static str, value: u32 },
+    /// A UCS-2 string could not be encoded or decoded.
+    StringEncoding(ucs2::Error),
+    /// A message relies upon an SDMX capability that was not negotiated with the peer.
+    CapabilityNotNegotiated { capability: u16 },
+    /// A DMX channel lies beyond the last channel of the 65_536 channel wide universe.
+    ChannelOutOfRange { channel: usize },
+    /// There are more elements than the named count field is able to describe.
+    CountOverflow {
+        field: &'static str,
+        count: usize,
+        max: usize,
+    },
+    /// A count field does not match the number of elements that follow it.
+    CountMismatch {
+        field: &'static str,
+        count: usize,
+        actual: usize,
+    },
+    /// A fragment index is not below the fragment count, or the fragment count differs from that
+    /// of the other fragments of the same frame.
+    InvalidFragment {
+        frame_index: u32,
+        fragment_index: u16,
+        fragment_count: u16,
+    },
+    /// A fragment extends beyond the maximum frame size accepted by the receiver.
+    FrameTooLarge { size: usize, max: usize },
+}
+
+impl Error {
+    /// Shorthand for producing an `InvalidDiscriminant` error.
+    pub(crate) fn invalid_discriminant<T: Into<u32>>(ty: &'static str, value: T) -> Self {
+        Error::InvalidDiscriminant {
+            ty,
+            value: value.into(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref err) => write!(f, "{}", err),
+            Error::BadCookie { found } => {
+                write!(f, "expected the \"CITP\" cookie, found {}", found)
+            }
+            Error::UnsupportedVersion { major, minor } => {
+                write!(f, "unsupported CITP version {}.{}", major, minor)
+            }
+            Error::Truncated { expected, actual } => write!(
+                f,
+                "truncated payload: expected {} bytes, found {}",
+                expected, actual
+            ),
+            Error::VersionMismatch {
+                expected_major,
+                expected_minor,
+                major,
+                minor,
+            } => write!(
+                f,
+                "expected MSEX version {}.{}, found {}.{}",
+                expected_major, expected_minor, major, minor
+            ),
+            Error::NoCommonVersion => write!(f, "no common MSEX version is supported"),
+            Error::UnknownLayer { layer } => {
+                write!(f, "unknown layer cookie {}", layer)
+            }
+            Error::UnknownMessage { layer, cookie } => write!(
+                f,
+                "unknown message cookie {} within layer {}",
+                cookie, layer
+            ),
+            Error::ContentTypeMismatch { expected, found } => {
+                write!(f, "expected content type {}, found {}", expected, found)
+            }
+            Error::MessageSizeMismatch {
+                message_size,
+                actual,
+            } => write!(
+                f,
+                "`message_size` is {} but the message was {} bytes",
+                message_size, actual
+            ),
+            Error::MessageTooLarge { message_size, max } => write!(
+                f,
+                "`message_size` is {} but at most {} bytes are accepted",
+                message_size, max
+            ),
+            Error::InvalidMessagePart {
+                message_part,
+                message_part_count,
+            } => write!(
+                f,
+                "`message_part` is {} but the `message_part_count` is {}",
+                message_part, message_part_count
+            ),
+            Error::InvalidDiscriminant { ty, value } => {
+                write!(f, "invalid `{}` value: {}", ty, value)
+            }
+            Error::StringEncoding(ref err) => write!(f, "invalid UCS-2 string: {}", err),
+            Error::CapabilityNotNegotiated { capability } => write!(
+                f,
+                "SDMX capability {} was not negotiated with the peer",
+                capability
+            ),
+            Error::ChannelOutOfRange { channel } => write!(
+                f,
+                "channel {} lies beyond the last channel of the universe",
+                channel
+            ),
+            Error::CountOverflow { field, count, max } => {
+                write!(f, "`{}` is {} but may be at most {}", field, count, max)
+            }
+            Error::CountMismatch {
+                field,
+                count,
+                actual,
+            } => write!(
+                f,
+                "`{}` is {} but {} elements were provided",
+                field, count, actual
+            ),
+            Error::InvalidFragment {
+                frame_index,
+                fragment_index,
+                fragment_count,
+            } => write!(
+                f,
+                "invalid fragment {} of {} for frame {}",
+                fragment_index, fragment_count, frame_index
+            ),
+            Error::FrameTooLarge { size, max } => write!(
+                f,
+                "frame of at least {} bytes exceeds the maximum of {} bytes",
+                size, max
+            ),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        // Unwrap errors that were wrapped for the sake of passing through an `io::Read`.
+        if err.get_ref().is_some_and(|inner| inner.is::<Error>()) {
+            let inner = err.into_inner().expect("checked above");
+            return *inner.downcast::<Error>().expect("checked above");
+        }
+        Error::Io(err)
+    }
+}
+
+impl From<ucs2::Error> for Error {
+    fn from(err: ucs2::Error) -> Self {
+        Error::StringEncoding(err)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(err: Error) -> Self {
+        match err {
+            Error::Io(err) => err,
+            Error::Truncated { .. } => io::Error::new(io::ErrorKind::UnexpectedEof, err),
+            err => io::Error::new(io::ErrorKind::InvalidData, err),
+        }
+    }
+}
@@ -1 +1,5 @@
+pub use self::error::{Error, Result};
+
+mod error;
+pub mod net;
 pub mod protocol;
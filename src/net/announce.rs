@@ -0,0 +1,170 @@
+use crate::protocol::{pinf, WriteBytes};
+use crate::Result;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::ffi::CString;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Periodically multicasts a PINF `PLoc` message describing the local peer.
+///
+/// The `pinf` docs recommend sending the `PLoc` message to both the old and the official
+/// multicast addresses while the transition between the two is ongoing. This is the default.
+///
+/// The announcer may optionally also broadcast a `PNam` message to the `OLD_BROADCAST_PORT` for
+/// peers that predate the `PLoc` message.
+#[derive(Debug)]
+pub struct PeerAnnouncer {
+    socket: UdpSocket,
+    ploc: pinf::PLoc,
+    interval: Duration,
+    targets: Targets,
+    port: u16,
+    pnam_broadcast: bool,
+    pnam_port: u16,
+    last_sent: Option<Instant>,
+}
+
+/// The multicast groups to which `PLoc` messages are sent.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Targets {
+    /// The official `MULTICAST_ADDR`.
+    Current,
+    /// The `OLD_MULTICAST_ADDR` used prior to early 2014.
+    Legacy,
+    /// Both addresses, as recommended during the transition.
+    #[default]
+    Both,
+}
+
+impl PeerAnnouncer {
+    /// The default interval between announcements.
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Announce the given peer location on the default multicast interface.
+    pub fn new(ploc: pinf::PLoc) -> io::Result<Self> {
+        Self::bind(Ipv4Addr::UNSPECIFIED, ploc)
+    }
+
+    /// Announce the given peer location via the given interface.
+    ///
+    /// Use `Ipv4Addr::LOCALHOST` as the interface to announce to peers on the local machine only.
+    /// The interface only selects the outgoing interface for multicast announcements. The socket
+    /// itself is bound to all interfaces so that the optional `PNam` broadcast may still be sent.
+    pub fn bind(interface: Ipv4Addr, ploc: pinf::PLoc) -> io::Result<Self> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0).into())?;
+        socket.set_multicast_if_v4(&interface)?;
+        // Allow peers on this machine to receive the announcements.
+        socket.set_multicast_loop_v4(true)?;
+        Ok(PeerAnnouncer {
+            socket: socket.into(),
+            ploc,
+            interval: Self::DEFAULT_INTERVAL,
+            targets: Targets::default(),
+            port: pinf::MULTICAST_PORT,
+            pnam_broadcast: false,
+            pnam_port: pinf::OLD_BROADCAST_PORT,
+            last_sent: None,
+        })
+    }
+
+    /// Specify the interval between announcements.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Specify the multicast groups to which announcements are sent.
+    pub fn with_targets(mut self, targets: Targets) -> Self {
+        self.targets = targets;
+        self
+    }
+
+    /// Specify the port to which announcements are multicast. `MULTICAST_PORT` by default.
+    pub fn with_port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// Also broadcast a legacy `PNam` message to the `OLD_BROADCAST_PORT` with each announcement.
+    pub fn with_pnam_broadcast(mut self, enabled: bool) -> io::Result<Self> {
+        self.socket.set_broadcast(enabled)?;
+        self.pnam_broadcast = enabled;
+        Ok(self)
+    }
+
+    /// Specify the port to which `PNam` messages are broadcast. `OLD_BROADCAST_PORT` by default.
+    pub fn with_pnam_port(mut self, port: u16) -> Self {
+        self.pnam_port = port;
+        self
+    }
+
+    /// The peer location that is announced.
+    pub fn ploc(&self) -> &pinf::PLoc {
+        &self.ploc
+    }
+
+    /// Update the display state of the peer, e.g. "Idle" or "Running".
+    ///
+    /// The new state is sent with the next call to `poll`.
+    pub fn set_state(&mut self, state: CString) {
+        if self.ploc.state != state {
+            self.ploc.state = state;
+            self.last_sent = None;
+        }
+    }
+
+    /// Replace the announced peer location.
+    ///
+    /// The new location is sent with the next call to `poll`.
+    pub fn set_ploc(&mut self, ploc: pinf::PLoc) {
+        self.ploc = ploc;
+        self.last_sent = None;
+    }
+
+    /// Send an announcement if the interval has elapsed since the last.
+    ///
+    /// Returns whether or not an announcement was sent.
+    pub fn poll(&mut self, now: Instant) -> Result<bool> {
+        let due = match self.last_sent {
+            None => true,
+            Some(last_sent) => now.saturating_duration_since(last_sent) >= self.interval,
+        };
+        if due {
+            self.announce(now)?;
+        }
+        Ok(due)
+    }
+
+    /// Send an announcement immediately.
+    pub fn announce(&mut self, now: Instant) -> Result<()> {
+        let mut bytes = vec![];
+        bytes.write_bytes(pinf::Message::new(self.ploc.clone()))?;
+        let groups: &[[u8; 4]] = match self.targets {
+            Targets::Current => &[pinf::MULTICAST_ADDR],
+            Targets::Legacy => &[pinf::OLD_MULTICAST_ADDR],
+            Targets::Both => &[pinf::MULTICAST_ADDR, pinf::OLD_MULTICAST_ADDR],
+        };
+        for &group in groups {
+            let addr = SocketAddr::from((group, self.port));
+            self.socket.send_to(&bytes, addr)?;
+        }
+        if self.pnam_broadcast {
+            let pnam = pinf::PNam {
+                name: self.ploc.name.clone(),
+            };
+            bytes.clear();
+            bytes.write_bytes(pinf::Message::new(pnam))?;
+            let addr = SocketAddr::from((Ipv4Addr::BROADCAST, self.pnam_port));
+            self.socket.send_to(&bytes, addr)?;
+        }
+        self.last_sent = Some(now);
+        Ok(())
+    }
+
+    /// The address of the underlying socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
@@ -0,0 +1,220 @@
+use crate::protocol::{pinf, Packet, PinfMessage};
+use crate::Result;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4, UdpSocket};
+use std::time::{Duration, Instant};
+
+/// Listens for PINF `PLoc` announcements and maintains a table of the peers that are present.
+///
+/// The socket joins both the official and the old multicast groups on the given interface so
+/// that both current and legacy peers are discovered. The socket is non-blocking - `poll` should
+/// be called regularly in order to process received announcements and to expire silent peers.
+#[derive(Debug)]
+pub struct Discovery {
+    socket: UdpSocket,
+    peers: PeerTable,
+    buffer: Vec<u8>,
+}
+
+/// The set of peers discovered via their `PLoc` announcements.
+///
+/// Peers are identified by the IP address from which their announcements are sent, as the source
+/// port of an announcement may change between sockets or restarts. Peers are removed after they
+/// have remained silent for longer than the timeout.
+#[derive(Clone, Debug)]
+pub struct PeerTable {
+    timeout: Duration,
+    peers: HashMap<IpAddr, Peer>,
+}
+
+/// A peer described by its most recent `PLoc` announcement.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Peer {
+    /// The IP address from which the peer's announcements are sent.
+    pub ip: IpAddr,
+    /// The port on which the peer accepts TCP connections. `0` if not listening.
+    pub listening_tcp_port: u16,
+    /// The kind of peer.
+    pub kind: pinf::PeerKind,
+    /// The display name of the peer.
+    pub name: String,
+    /// The display state of the peer, e.g. "Idle" or "Running".
+    pub state: String,
+    /// The moment at which the last announcement was received.
+    pub last_seen: Instant,
+}
+
+/// Changes to the `PeerTable`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerEvent {
+    /// An announcement was received from a new peer.
+    Added(Peer),
+    /// A known peer announced a change to its port, kind, name or state.
+    Updated(Peer),
+    /// A peer has remained silent for longer than the timeout.
+    Removed(Peer),
+}
+
+impl Discovery {
+    /// Listen for announcements multicast to the default port on any interface.
+    pub fn new() -> io::Result<Self> {
+        Self::bind(Ipv4Addr::UNSPECIFIED, pinf::MULTICAST_PORT)
+    }
+
+    /// Listen for announcements multicast to the given port on the given interface.
+    ///
+    /// Use `Ipv4Addr::LOCALHOST` as the interface to discover peers on the local machine only.
+    pub fn bind(interface: Ipv4Addr, port: u16) -> io::Result<Self> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        // Allow other CITP peers on this machine to listen on the same port.
+        socket.set_reuse_address(true)?;
+        socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, port).into())?;
+        for group in [pinf::MULTICAST_ADDR, pinf::OLD_MULTICAST_ADDR] {
+            socket.join_multicast_v4(&group.into(), &interface)?;
+        }
+        socket.set_nonblocking(true)?;
+        Ok(Discovery {
+            socket: socket.into(),
+            peers: PeerTable::default(),
+            buffer: vec![0; u16::MAX as usize],
+        })
+    }
+
+    /// Specify how long a peer may remain silent before it is removed.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.peers.timeout = timeout;
+        self
+    }
+
+    /// Process all announcements received since the last call and remove expired peers.
+    ///
+    /// Datagrams that are not valid PINF `PLoc` messages are ignored.
+    pub fn poll(&mut self, now: Instant) -> Result<Vec<PeerEvent>> {
+        let mut events = vec![];
+        loop {
+            let (len, addr) = match self.socket.recv_from(&mut self.buffer) {
+                Ok(received) => received,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => return Err(err.into()),
+            };
+            if let Ok(Packet::Pinf(PinfMessage::PLoc(msg))) = Packet::decode(&self.buffer[..len]) {
+                events.extend(self.peers.update(addr, &msg.message, now));
+            }
+        }
+        events.extend(self.peers.remove_expired(now));
+        Ok(events)
+    }
+
+    /// The peers that are currently known.
+    pub fn peers(&self) -> &PeerTable {
+        &self.peers
+    }
+
+    /// The address of the underlying socket.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl PeerTable {
+    /// The default duration a peer may remain silent before it is removed.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Create an empty table that removes peers after `timeout` has elapsed without an
+    /// announcement.
+    pub fn new(timeout: Duration) -> Self {
+        PeerTable {
+            timeout,
+            peers: HashMap::new(),
+        }
+    }
+
+    /// Update the table with an announcement received from `addr`.
+    ///
+    /// Returns `None` if the peer at the IP address of `addr` is already known and its
+    /// announcement has not changed.
+    pub fn update(
+        &mut self,
+        addr: SocketAddr,
+        ploc: &pinf::PLoc,
+        now: Instant,
+    ) -> Option<PeerEvent> {
+        let ip = addr.ip();
+        let peer = Peer {
+            ip,
+            listening_tcp_port: ploc.listening_tcp_port,
+            kind: ploc.kind.clone(),
+            name: ploc.name.to_string_lossy().into_owned(),
+            state: ploc.state.to_string_lossy().into_owned(),
+            last_seen: now,
+        };
+        match self.peers.insert(ip, peer.clone()) {
+            None => Some(PeerEvent::Added(peer)),
+            Some(prev) => {
+                let changed = prev.listening_tcp_port != peer.listening_tcp_port
+                    || prev.kind != peer.kind
+                    || prev.name != peer.name
+                    || prev.state != peer.state;
+                if changed {
+                    Some(PeerEvent::Updated(peer))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Remove all peers that have not announced themselves within the timeout.
+    pub fn remove_expired(&mut self, now: Instant) -> Vec<PeerEvent> {
+        let timeout = self.timeout;
+        let expired: Vec<IpAddr> = self
+            .peers
+            .values()
+            .filter(|peer| now.saturating_duration_since(peer.last_seen) >= timeout)
+            .map(|peer| peer.ip)
+            .collect();
+        expired
+            .into_iter()
+            .filter_map(|ip| self.peers.remove(&ip))
+            .map(PeerEvent::Removed)
+            .collect()
+    }
+
+    /// The peer whose announcements are sent from the given IP address.
+    pub fn get(&self, ip: &IpAddr) -> Option<&Peer> {
+        self.peers.get(ip)
+    }
+
+    /// An iterator yielding all known peers in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Peer> {
+        self.peers.values()
+    }
+
+    /// The number of known peers.
+    pub fn len(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// Whether or not there are no known peers.
+    pub fn is_empty(&self) -> bool {
+        self.peers.is_empty()
+    }
+}
+
+impl Peer {
+    /// The address at which the peer accepts TCP connections, if it is listening.
+    pub fn tcp_addr(&self) -> Option<SocketAddr> {
+        match self.listening_tcp_port {
+            0 => None,
+            port => Some(SocketAddr::new(self.ip, port)),
+        }
+    }
+}
+
+impl Default for PeerTable {
+    fn default() -> Self {
+        PeerTable::new(Self::DEFAULT_TIMEOUT)
+    }
+}
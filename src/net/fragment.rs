@@ -0,0 +1,259 @@
+use crate::protocol::{Header, ReadBytes, WriteBytes};
+use crate::{Error, Result};
+use std::collections::hash_map::{Entry, HashMap};
+use std::hash::Hash;
+use std::mem;
+use std::time::{Duration, Instant};
+
+/// The size of the base header that begins every message part.
+const HEADER_SIZE: usize = mem::size_of::<Header>();
+
+/// Splits encoded messages into parts that each fit within a maximum transmission unit.
+///
+/// Each part begins with a copy of the original base header with the `message_size`,
+/// `message_part_count` and `message_part` fields updated to describe the part. The bytes
+/// following the base header (the second layer header and the message itself) are divided
+/// between the parts in order.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Fragmenter {
+    mtu: usize,
+}
+
+/// Reassembles messages from their parts.
+///
+/// Parts are grouped by the sender along with the request index and content type of their base
+/// header. Parts may arrive in any order and duplicate parts are ignored. A part that differs
+/// from one already received for the same group begins a new message, discarding the old one.
+/// Messages that remain incomplete for longer than the timeout are discarded.
+///
+/// The sender `S` is typically the `SocketAddr` from which the part was received.
+#[derive(Clone, Debug)]
+pub struct Reassembler<S> {
+    timeout: Duration,
+    partials: HashMap<PartialKey<S>, Partial>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PartialKey<S> {
+    sender: S,
+    request_index: u16,
+    content_type: u32,
+}
+
+#[derive(Clone, Debug)]
+struct Partial {
+    header: Header,
+    parts: Vec<Option<Vec<u8>>>,
+    remaining: usize,
+    last_received: Instant,
+}
+
+impl Fragmenter {
+    /// Leaves room for IP and UDP headers within a typical 1500 byte ethernet frame.
+    pub const DEFAULT_MTU: usize = 1400;
+
+    /// Create a `Fragmenter` producing parts of at most `mtu` bytes, including the base header.
+    ///
+    /// **Panics** if `mtu` does not leave room for at least one byte following the base header.
+    pub fn new(mtu: usize) -> Self {
+        assert!(
+            mtu > HEADER_SIZE,
+            "`mtu` must be greater than the base header size"
+        );
+        Fragmenter { mtu }
+    }
+
+    /// The maximum size of each part in bytes.
+    pub fn mtu(&self) -> usize {
+        self.mtu
+    }
+
+    /// Split the encoded `message` into parts.
+    ///
+    /// Messages that already fit within the MTU are returned as a single part unchanged.
+    pub fn fragment(&self, message: &[u8]) -> Result<Vec<Vec<u8>>> {
+        let header: Header = (&message[..]).read_bytes()?;
+        let message = message_bytes(&header, message)?;
+        if message.len() <= self.mtu {
+            return Ok(vec![message.to_vec()]);
+        }
+
+        let chunks = message[HEADER_SIZE..].chunks(self.mtu - HEADER_SIZE);
+        let part_count = chunks.len();
+        if part_count > u16::MAX as usize {
+            return Err(Error::CountOverflow {
+                field: "message_part_count",
+                count: part_count,
+                max: u16::MAX as usize,
+            });
+        }
+
+        let mut parts = Vec::with_capacity(part_count);
+        for (i, chunk) in chunks.enumerate() {
+            let mut part_header = header;
+            part_header.message_size = (HEADER_SIZE + chunk.len()) as u32;
+            part_header.message_part_count = part_count as u16;
+            part_header.message_part = i as u16;
+            let mut part = Vec::with_capacity(HEADER_SIZE + chunk.len());
+            part.write_bytes(part_header)?;
+            part.extend_from_slice(chunk);
+            parts.push(part);
+        }
+        Ok(parts)
+    }
+}
+
+impl<S> Reassembler<S>
+where
+    S: Hash + Eq,
+{
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Create a `Reassembler` that discards incomplete messages after `timeout` has elapsed
+    /// without receiving any new parts.
+    pub fn new(timeout: Duration) -> Self {
+        Reassembler {
+            timeout,
+            partials: HashMap::new(),
+        }
+    }
+
+    /// Submit a received message part.
+    ///
+    /// Returns the complete message once all of its parts have been received. The returned
+    /// message has a single base header describing the whole message and may be decoded via
+    /// `Packet::decode`. Unfragmented messages are returned immediately.
+    pub fn push(&mut self, sender: S, part: &[u8], now: Instant) -> Result<Option<Vec<u8>>> {
+        self.remove_expired(now);
+
+        let header: Header = (&part[..]).read_bytes()?;
+        let part = message_bytes(&header, part)?;
+        if header.message_part_count <= 1 {
+            return Ok(Some(part.to_vec()));
+        }
+        if header.message_part >= header.message_part_count {
+            return Err(Error::InvalidMessagePart {
+                message_part: header.message_part,
+                message_part_count: header.message_part_count,
+            });
+        }
+
+        let key = PartialKey {
+            sender,
+            request_index: header.kind.get(),
+            content_type: header.content_type,
+        };
+        let part_count = header.message_part_count as usize;
+        let data = &part[HEADER_SIZE..];
+        let new_partial = || Partial {
+            header,
+            parts: vec![None; part_count],
+            remaining: part_count,
+            last_received: now,
+        };
+        let mut entry = match self.partials.entry(key) {
+            Entry::Vacant(entry) => entry.insert_entry(new_partial()),
+            Entry::Occupied(mut entry) => {
+                // A new message has begun under the same key if the headers differ or if a part
+                // that was already received arrives with different contents.
+                if !entry.get().is_continued_by(&header, data) {
+                    entry.insert(new_partial());
+                }
+                entry
+            }
+        };
+
+        let partial = entry.get_mut();
+        partial.last_received = now;
+        let slot = &mut partial.parts[header.message_part as usize];
+        if slot.is_none() {
+            *slot = Some(data.to_vec());
+            partial.remaining -= 1;
+        }
+        if partial.remaining > 0 {
+            return Ok(None);
+        }
+
+        let partial = entry.remove();
+        let payload: Vec<u8> = partial.parts.into_iter().flatten().flatten().collect();
+        let mut message_header = partial.header;
+        message_header.message_size = (HEADER_SIZE + payload.len()) as u32;
+        message_header.message_part_count = 1;
+        message_header.message_part = 0;
+        let mut message = Vec::with_capacity(HEADER_SIZE + payload.len());
+        message.write_bytes(message_header)?;
+        message.extend(payload);
+        Ok(Some(message))
+    }
+
+    /// Discard all incomplete messages that have not received a part within the timeout.
+    ///
+    /// Returns the number of messages that were discarded. This is also called automatically
+    /// each time a part is pushed.
+    pub fn remove_expired(&mut self, now: Instant) -> usize {
+        let timeout = self.timeout;
+        let len = self.partials.len();
+        self.partials
+            .retain(|_, partial| now.saturating_duration_since(partial.last_received) < timeout);
+        len - self.partials.len()
+    }
+
+    /// The number of messages that are awaiting further parts.
+    pub fn len(&self) -> usize {
+        self.partials.len()
+    }
+
+    /// Whether or not there are no messages awaiting further parts.
+    pub fn is_empty(&self) -> bool {
+        self.partials.is_empty()
+    }
+}
+
+impl Partial {
+    /// Whether or not the part described by `header` and carrying `data` belongs to this message.
+    fn is_continued_by(&self, header: &Header, data: &[u8]) -> bool {
+        let stored = &self.header;
+        let same_header = stored.cookie == header.cookie
+            && stored.version_major == header.version_major
+            && stored.version_minor == header.version_minor
+            && stored.message_part_count == header.message_part_count;
+        same_header
+            && match self.parts[header.message_part as usize] {
+                Some(ref received) => received[..] == data[..],
+                None => true,
+            }
+    }
+}
+
+impl Default for Fragmenter {
+    fn default() -> Self {
+        Fragmenter::new(Self::DEFAULT_MTU)
+    }
+}
+
+impl<S> Default for Reassembler<S>
+where
+    S: Hash + Eq,
+{
+    fn default() -> Self {
+        Reassembler::new(Self::DEFAULT_TIMEOUT)
+    }
+}
+
+/// The bytes of the message described by `header`, excluding any trailing bytes.
+fn message_bytes<'a>(header: &Header, bytes: &'a [u8]) -> Result<&'a [u8]> {
+    let message_size = header.message_size as usize;
+    if bytes.len() < message_size {
+        return Err(Error::Truncated {
+            expected: message_size,
+            actual: bytes.len(),
+        });
+    }
+    if message_size < HEADER_SIZE {
+        return Err(Error::Truncated {
+            expected: HEADER_SIZE,
+            actual: message_size,
+        });
+    }
+    Ok(&bytes[..message_size])
+}
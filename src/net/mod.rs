@@ -0,0 +1,22 @@
+//! ## Networking
+//!
+//! Types for communicating the CITP protocol over a network.
+//!
+//! CITP peers discover each other via UDP multicast (see the `protocol::pinf` docs) before
+//! establishing a peer-to-peer TCP connection over which most messages are sent. Some messages,
+//! such as CAEX laser feed frames and MSEX stream frames, are multicast over UDP instead.
+
+/// Periodic announcement of the local peer via multicast PINF messages.
+pub mod announce;
+
+/// Discovery of peers via their multicast PINF announcements.
+pub mod discovery;
+
+/// Splitting of oversized messages into multiple parts and the reassembly of received parts.
+pub mod fragment;
+
+/// Allocation of request indices and the matching of responses to their requests.
+pub mod request;
+
+/// Framing of CITP messages over peer-to-peer TCP connections.
+pub mod tcp;
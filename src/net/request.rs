@@ -0,0 +1,169 @@
+use crate::protocol::{Header, Kind, Packet, RequestCounter, RequestIndex};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Tracks requests that are awaiting a response.
+///
+/// Each request is assigned a unique request index which is written to the `Kind` field of its
+/// base header. Received packets are then passed to `complete` in order to match them against
+/// the request whose index they carry as `in_response_to`. Requests that have not received a
+/// response before their timeout elapses may be collected via `remove_expired`.
+///
+/// `T` is any data the user wishes to associate with each request, e.g. the request message
+/// itself or the kind of response that is expected.
+#[derive(Clone, Debug)]
+pub struct PendingRequests<T> {
+    counter: RequestCounter,
+    pending: HashMap<RequestIndex, Pending<T>>,
+}
+
+#[derive(Clone, Debug)]
+struct Pending<T> {
+    data: T,
+    deadline: Instant,
+}
+
+/// The result of matching a received packet against the pending requests.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Received<T> {
+    /// A response to a pending request.
+    Response {
+        index: RequestIndex,
+        request: T,
+        packet: Packet,
+    },
+    /// A negative acknowledgement of a pending request.
+    Nack {
+        index: RequestIndex,
+        request: T,
+        packet: Packet,
+    },
+    /// A packet that does not respond to any pending request.
+    Unsolicited(Packet),
+}
+
+impl<T> PendingRequests<T> {
+    /// Create an empty tracker whose first request index will be `1`.
+    pub fn new() -> Self {
+        PendingRequests {
+            counter: RequestCounter::new(),
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Register a new request that expires after `timeout` has elapsed.
+    ///
+    /// Allocates a request index that is not already pending and writes it to the `kind` field
+    /// of the given base header. The header should be written to the request message before it
+    /// is sent.
+    ///
+    /// Returns `None` without modifying the header if all 65_535 request indices are pending. Room
+    /// may be made via `remove_expired` or `cancel`.
+    pub fn insert(
+        &mut self,
+        citp_header: &mut Header,
+        data: T,
+        timeout: Duration,
+        now: Instant,
+    ) -> Option<RequestIndex> {
+        if self.pending.len() >= u16::MAX as usize {
+            return None;
+        }
+        // Skip indices still in use by long-lived requests.
+        let mut index = self.counter.next_index();
+        while self.pending.contains_key(&index) {
+            index = self.counter.next_index();
+        }
+        citp_header.kind = Kind::request(index);
+        let deadline = now + timeout;
+        self.pending.insert(index, Pending { data, deadline });
+        Some(index)
+    }
+
+    /// Match a received packet against the pending requests.
+    ///
+    /// If the `in_response_to` field of the packet's base header refers to a pending request, the
+    /// request is removed and returned along with the packet. CAEX and MSEX `Nack` messages are
+    /// distinguished from regular responses.
+    ///
+    /// `Unknown` packets whose payload does not begin with a valid base header are unsolicited.
+    ///
+    /// Note that requests received from the remote peer carry their own request index within the
+    /// same field. Packets that are known to be requests should not be passed to this method.
+    pub fn complete(&mut self, packet: Packet) -> Received<T> {
+        let in_response_to = packet
+            .citp_header()
+            .ok()
+            .and_then(|header| header.kind.in_response_to());
+        let index = match in_response_to {
+            Some(index) => index,
+            None => return Received::Unsolicited(packet),
+        };
+        let request = match self.pending.remove(&index) {
+            Some(pending) => pending.data,
+            None => return Received::Unsolicited(packet),
+        };
+        if packet.is_nack() {
+            Received::Nack {
+                index,
+                request,
+                packet,
+            }
+        } else {
+            Received::Response {
+                index,
+                request,
+                packet,
+            }
+        }
+    }
+
+    /// Remove and return all requests whose timeout has elapsed, ordered by request index.
+    pub fn remove_expired(&mut self, now: Instant) -> Vec<(RequestIndex, T)> {
+        let expired: Vec<RequestIndex> = self
+            .pending
+            .iter()
+            .filter(|(_, pending)| pending.deadline <= now)
+            .map(|(&index, _)| index)
+            .collect();
+        let mut removed: Vec<_> = expired
+            .into_iter()
+            .filter_map(|index| self.cancel(index).map(|data| (index, data)))
+            .collect();
+        removed.sort_by_key(|&(index, _)| index);
+        removed
+    }
+
+    /// Stop tracking the request with the given index, returning its associated data.
+    pub fn cancel(&mut self, index: RequestIndex) -> Option<T> {
+        self.pending.remove(&index).map(|pending| pending.data)
+    }
+
+    /// The data associated with the pending request with the given index.
+    pub fn get(&self, index: RequestIndex) -> Option<&T> {
+        self.pending.get(&index).map(|pending| &pending.data)
+    }
+
+    /// The earliest instant at which a pending request will expire.
+    ///
+    /// Useful for determining how long to block while waiting for a response.
+    pub fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().map(|pending| pending.deadline).min()
+    }
+
+    /// The number of requests awaiting a response.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether or not there are no requests awaiting a response.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+impl<T> Default for PendingRequests<T> {
+    fn default() -> Self {
+        PendingRequests::new()
+    }
+}
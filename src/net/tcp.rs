@@ -0,0 +1,139 @@
+use crate::protocol::{Header, Packet, ReadBytes, WriteBytes, WriteToBytes};
+use crate::{Error, Result};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use std::{mem, time::Duration};
+
+/// The size of the base header that begins every message.
+const HEADER_SIZE: usize = mem::size_of::<Header>();
+
+/// A peer-to-peer CITP connection over TCP.
+///
+/// Messages are framed using the `message_size` field of the base header, allowing any number of
+/// messages to arrive within a single TCP segment or a single message to span many segments.
+///
+/// Bytes of partially received messages are retained between calls to `recv`, so it is safe to
+/// use a read timeout or a non-blocking stream and to call `recv` again after a `WouldBlock` or
+/// `TimedOut` error. Any other IO error leaves the stream in an unknown state and the session
+/// should be closed.
+///
+/// If a received base header is invalid or describes a message larger than the maximum message
+/// size, the position of the following message within the stream cannot be known. The invalid
+/// header is retained and every following call to `recv` returns the same error without reading
+/// from the stream. The session should be closed.
+#[derive(Debug)]
+pub struct Session {
+    stream: TcpStream,
+    writer: io::BufWriter<TcpStream>,
+    received: Vec<u8>,
+    max_message_size: usize,
+}
+
+impl Session {
+    /// The default maximum size of a received message in bytes.
+    pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+    /// Wrap an established TCP stream.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        let writer = io::BufWriter::new(stream.try_clone()?);
+        Ok(Session {
+            stream,
+            writer,
+            received: vec![],
+            max_message_size: Self::DEFAULT_MAX_MESSAGE_SIZE,
+        })
+    }
+
+    /// Connect to the peer listening at the given address.
+    ///
+    /// The address is typically determined by the `listening_tcp_port` of a received PINF
+    /// `PLoc` message.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(addr)?;
+        Session::new(stream)
+    }
+
+    /// Specify the maximum size of a received message, including the base header.
+    ///
+    /// The `message_size` of a received base header is determined by the peer. Receiving a
+    /// message larger than `max_message_size` produces a `MessageTooLarge` error rather than
+    /// buffering the message.
+    pub fn with_max_message_size(mut self, max_message_size: usize) -> Self {
+        self.max_message_size = max_message_size;
+        self
+    }
+
+    /// The maximum size of a received message in bytes.
+    pub fn max_message_size(&self) -> usize {
+        self.max_message_size
+    }
+
+    /// Write the whole message to the stream and flush it.
+    pub fn send<T: WriteToBytes>(&mut self, message: &T) -> Result<()> {
+        self.writer.write_bytes(message)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Block until the next whole message has been received and decode it.
+    ///
+    /// Returns an `UnexpectedEof` IO error if the peer closes the connection.
+    pub fn recv(&mut self) -> Result<Packet> {
+        let bytes = self.recv_bytes()?;
+        Packet::decode(&bytes)
+    }
+
+    /// Block until the next whole message has been received, returning its raw bytes.
+    ///
+    /// Useful for forwarding messages without decoding them.
+    pub fn recv_bytes(&mut self) -> Result<Vec<u8>> {
+        self.fill(HEADER_SIZE)?;
+        let header: Header = (&self.received[..]).read_bytes()?;
+        let message_size = header.message_size as usize;
+        if message_size < HEADER_SIZE {
+            return Err(Error::Truncated {
+                expected: HEADER_SIZE,
+                actual: message_size,
+            });
+        }
+        if message_size > self.max_message_size {
+            return Err(Error::MessageTooLarge {
+                message_size,
+                max: self.max_message_size,
+            });
+        }
+        self.fill(message_size)?;
+        let remaining = self.received.split_off(message_size);
+        Ok(mem::replace(&mut self.received, remaining))
+    }
+
+    /// The address of the remote peer.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Set the timeout used by `recv`. `None` blocks indefinitely.
+    pub fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.stream.set_read_timeout(timeout)
+    }
+
+    /// A reference to the underlying TCP stream.
+    pub fn stream(&self) -> &TcpStream {
+        &self.stream
+    }
+
+    /// Read from the stream until at least `len` bytes have been received.
+    fn fill(&mut self, len: usize) -> io::Result<()> {
+        while self.received.len() < len {
+            let remaining = (len - self.received.len()) as u64;
+            let n = (&self.stream)
+                .take(remaining)
+                .read_to_end(&mut self.received)?;
+            if n == 0 {
+                let err_msg = "the connection was closed by the peer";
+                return Err(io::Error::new(io::ErrorKind::UnexpectedEof, err_msg));
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,7 +1,9 @@
 use crate::protocol::{
-    self, ReadBytesExt, ReadFromBytes, SizeBytes, Ucs2, WriteBytes, WriteBytesExt, WriteToBytes, LE,
+    self, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, Ucs2, WriteBytes,
+    WriteBytesExt, WriteToBytes, LE,
 };
-use std::{borrow::Cow, io, mem};
+use crate::{Error, Result};
+use std::{borrow::Cow, mem};
 
 /// The CAEX layer provides a standard, single, header used at the start of all CAEX packets.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -31,14 +33,26 @@ pub enum NackReason {
     RequestRefused = 0x03,
 }
 
-impl From<u8> for NackReason {
-    fn from(orig: u8) -> Self {
+impl TryFrom<u8> for NackReason {
+    type Error = Error;
+    fn try_from(orig: u8) -> Result<Self> {
         match orig {
-            0x00 => NackReason::UnknownRequest,
-            0x01 => NackReason::IncorrectRequest,
-            0x02 => NackReason::InternalError,
-            0x03 => NackReason::RequestRefused,
-            _ => unreachable!(),
+            0x00 => Ok(NackReason::UnknownRequest),
+            0x01 => Ok(NackReason::IncorrectRequest),
+            0x02 => Ok(NackReason::InternalError),
+            0x03 => Ok(NackReason::RequestRefused),
+            _ => Err(Error::invalid_discriminant("NackReason", orig)),
+        }
+    }
+}
+
+impl From<NackReason> for u8 {
+    fn from(original: NackReason) -> u8 {
+        match original {
+            NackReason::UnknownRequest => 0x00,
+            NackReason::IncorrectRequest => 0x01,
+            NackReason::InternalError => 0x02,
+            NackReason::RequestRefused => 0x03,
         }
     }
 }
@@ -79,6 +93,7 @@ pub struct Message<T> {
 
 /// This message is sent unsolicited by both Capture and the peer when a show/project is opened and/or the user
 /// wishes to enable show synchronization
+#[allow(clippy::empty_line_after_doc_comments)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct EnterShow {
@@ -120,13 +135,14 @@ pub enum FixtureListMessageType {
     ExchangeFixture = 0x02,
 }
 
-impl From<u8> for FixtureListMessageType {
-    fn from(orig: u8) -> Self {
+impl TryFrom<u8> for FixtureListMessageType {
+    type Error = Error;
+    fn try_from(orig: u8) -> Result<Self> {
         match orig {
-            0x00 => FixtureListMessageType::ExistingPatchList,
-            0x01 => FixtureListMessageType::NewFixture,
-            0x02 => FixtureListMessageType::ExchangeFixture,
-            _ => unreachable!(),
+            0x00 => Ok(FixtureListMessageType::ExistingPatchList),
+            0x01 => Ok(FixtureListMessageType::NewFixture),
+            0x02 => Ok(FixtureListMessageType::ExchangeFixture),
+            _ => Err(Error::invalid_discriminant("FixtureListMessageType", orig)),
         }
     }
 }
@@ -153,16 +169,17 @@ pub enum IdentifierType {
     RDMManufacturerId = 0x05, // u16
 }
 
-impl From<u8> for IdentifierType {
-    fn from(orig: u8) -> Self {
+impl TryFrom<u8> for IdentifierType {
+    type Error = Error;
+    fn try_from(orig: u8) -> Result<Self> {
         match orig {
-            0x00 => IdentifierType::RDMDeviceModelId,
-            0x01 => IdentifierType::RDMPersonalityId,
-            0x02 => IdentifierType::AtlaBaseFixtureId,
-            0x03 => IdentifierType::AtlaBaseModeId,
-            0x04 => IdentifierType::CaptureInstanceId,
-            0x05 => IdentifierType::RDMManufacturerId,
-            _ => unreachable!(),
+            0x00 => Ok(IdentifierType::RDMDeviceModelId),
+            0x01 => Ok(IdentifierType::RDMPersonalityId),
+            0x02 => Ok(IdentifierType::AtlaBaseFixtureId),
+            0x03 => Ok(IdentifierType::AtlaBaseModeId),
+            0x04 => Ok(IdentifierType::CaptureInstanceId),
+            0x05 => Ok(IdentifierType::RDMManufacturerId),
+            _ => Err(Error::invalid_discriminant("IdentifierType", orig)),
         }
     }
 }
@@ -303,6 +320,7 @@ impl<'a> FixtureConsoleStatus<'a> {
 
 /// This message is sent by Capture upon connection to determine what laser feeds are available. Receving this
 /// message is an indication of Capture's ability to understand CAEX laser feeds.
+#[allow(clippy::empty_line_after_doc_comments)]
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
 pub struct GetLaserFeedList {}
@@ -372,6 +390,26 @@ impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"CAEX";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a CAEX message, filling in the CITP and CAEX headers.
+    pub fn new(message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let caex_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            caex_header,
+            message,
+        };
+        msg.caex_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl Nack {
     pub const CONTENT_TYPE: u32 = 0xFFFFFFFF;
 }
@@ -392,8 +430,56 @@ impl<'a> LaserFeedFrame<'a> {
     pub const CONTENT_TYPE: u32 = 0x00030200;
 }
 
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for Nack {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl ContentType for EnterShow {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl ContentType for LeaveShow {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl ContentType for FixtureListRequest {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for FixtureList<'a> {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for FixtureRemove<'a> {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for FixtureConsoleStatus<'a> {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl ContentType for GetLaserFeedList {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for LaserFeedList<'a> {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl ContentType for LaserFeedControl {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for LaserFeedFrame<'a> {
+    const COOKIE: Cookie = Cookie(Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         Ok(())
@@ -404,22 +490,46 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.caex_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
     }
 }
 
+impl WriteToBytes for Nack {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u8(self.reason.clone().into())?;
+        Ok(())
+    }
+}
+
 impl WriteToBytes for EnterShow {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         self.name.write_to_bytes(&mut writer)?;
         Ok(())
     }
 }
 
+impl WriteToBytes for LeaveShow {
+    fn write_to_bytes<W: WriteBytesExt>(&self, _writer: W) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl WriteToBytes for FixtureListRequest {
+    fn write_to_bytes<W: WriteBytesExt>(&self, _writer: W) -> Result<()> {
+        Ok(())
+    }
+}
+
 impl<'a> WriteToBytes for FixtureList<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count(
+            "fixture_count",
+            self.fixture_count as _,
+            self.fixtures.len(),
+        )?;
         writer.write_u8(self.message_type.into())?;
         writer.write_u16::<LE>(self.fixture_count)?;
         for fixture in self.fixtures.iter() {
@@ -430,13 +540,18 @@ impl<'a> WriteToBytes for FixtureList<'a> {
 }
 
 impl<'a> WriteToBytes for Fixture<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u32::<LE>(self.fixture_identifier)?;
         self.manufacturer_name.write_to_bytes(&mut writer)?;
         self.fixture_name.write_to_bytes(&mut writer)?;
         self.mode_name.write_to_bytes(&mut writer)?;
         writer.write_u16::<LE>(self.channel_count)?;
         writer.write_u8(self.is_dimmer)?;
+        protocol::check_count(
+            "identifier_count",
+            self.identifier_count as _,
+            self.identifiers.len(),
+        )?;
         writer.write_u8(self.identifier_count)?;
         for identifier in self.identifiers.iter() {
             identifier.write_to_bytes(&mut writer)?;
@@ -447,18 +562,17 @@ impl<'a> WriteToBytes for Fixture<'a> {
 }
 
 impl<'a> WriteToBytes for Identifier<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count("data_size", self.data_size as _, self.data.len())?;
         writer.write_u8(self.identifier_type.into())?;
         writer.write_u16::<LE>(self.data_size)?;
-        for i in 0..self.data_size {
-            writer.write_u8(self.data[i as usize])?;
-        }
+        writer.write_all(&self.data)?;
         Ok(())
     }
 }
 
 impl WriteToBytes for FixtureData {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.patched)?;
         writer.write_u8(self.universe)?;
         writer.write_u16::<LE>(self.universe_channel)?;
@@ -477,7 +591,12 @@ impl WriteToBytes for FixtureData {
 }
 
 impl<'a> WriteToBytes for FixtureRemove<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count(
+            "fixture_count",
+            self.fixture_count as _,
+            self.fixture_identifiers.len(),
+        )?;
         writer.write_u16::<LE>(self.fixture_count)?;
         for id in self.fixture_identifiers.iter() {
             writer.write_u32::<LE>(*id)?;
@@ -486,10 +605,41 @@ impl<'a> WriteToBytes for FixtureRemove<'a> {
     }
 }
 
+impl<'a> WriteToBytes for FixtureConsoleStatus<'a> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count(
+            "fixture_count",
+            self.fixture_count as _,
+            self.fixtures_state.len(),
+        )?;
+        writer.write_u16::<LE>(self.fixture_count)?;
+        for state in self.fixtures_state.iter() {
+            state.write_to_bytes(&mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytes for FixtureState {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u32::<LE>(self.fixture_identifier)?;
+        writer.write_u8(self.locked)?;
+        writer.write_u8(self.clearable)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for GetLaserFeedList {
+    fn write_to_bytes<W: WriteBytesExt>(&self, _writer: W) -> Result<()> {
+        Ok(())
+    }
+}
+
 impl<'a> WriteToBytes for LaserFeedList<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count("feed_count", self.feed_count as _, self.feed_names.len())?;
         writer.write_u32::<LE>(self.source_key)?;
-        writer.write_u8(self.feed_names.len() as _)?;
+        writer.write_u8(self.feed_count)?;
         for name in self.feed_names.iter() {
             name.write_to_bytes(&mut writer)?;
         }
@@ -498,7 +648,7 @@ impl<'a> WriteToBytes for LaserFeedList<'a> {
 }
 
 impl WriteToBytes for LaserFeedControl {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.feed_index)?;
         writer.write_u8(self.frame_rate)?;
         Ok(())
@@ -506,11 +656,12 @@ impl WriteToBytes for LaserFeedControl {
 }
 
 impl<'a> WriteToBytes for LaserFeedFrame<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u32::<LE>(self.source_key)?;
         writer.write_u8(self.feed_index)?;
         writer.write_u32::<LE>(self.frame_sequence)?;
-        writer.write_u16::<LE>(self.points.len() as _)?;
+        protocol::check_count("point_count", self.point_count as _, self.points.len())?;
+        writer.write_u16::<LE>(self.point_count)?;
         for p in self.points.iter() {
             p.write_to_bytes(&mut writer)?;
         }
@@ -519,7 +670,7 @@ impl<'a> WriteToBytes for LaserFeedFrame<'a> {
 }
 
 impl WriteToBytes for LaserPoint {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.x_low_byte)?;
         writer.write_u8(self.y_low_byte)?;
         writer.write_u8(self.xy_high_nibbles)?;
@@ -528,8 +679,66 @@ impl WriteToBytes for LaserPoint {
     }
 }
 
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let caex_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, caex_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&caex_header.citp_header, reader.count())?;
+        let msg = Message {
+            caex_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
+impl ReadFromBytes for Nack {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let reason = reader.read_u8()?.try_into()?;
+        Ok(Nack { reason })
+    }
+}
+
+impl ReadFromBytes for GetLaserFeedList {
+    fn read_from_bytes<R: ReadBytesExt>(_reader: R) -> Result<Self> {
+        Ok(GetLaserFeedList {})
+    }
+}
+
+impl ReadFromBytes for LaserFeedList<'static> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let source_key = reader.read_u32::<LE>()?;
+        let feed_count = reader.read_u8()?;
+        let feed_names = protocol::read_new_vec(reader, feed_count as _)?;
+        let laser_feed_list = LaserFeedList {
+            source_key,
+            feed_count,
+            feed_names: Cow::Owned(feed_names),
+        };
+        Ok(laser_feed_list)
+    }
+}
+
 impl ReadFromBytes for LaserFeedControl {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let feed_index = reader.read_u8()?;
         let frame_rate = reader.read_u8()?;
         let laser_feed_control = LaserFeedControl {
@@ -540,16 +749,62 @@ impl ReadFromBytes for LaserFeedControl {
     }
 }
 
+impl ReadFromBytes for LaserFeedFrame<'static> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let source_key = reader.read_u32::<LE>()?;
+        let feed_index = reader.read_u8()?;
+        let frame_sequence = reader.read_u32::<LE>()?;
+        let point_count = reader.read_u16::<LE>()?;
+        let points = protocol::read_new_vec(reader, point_count as _)?;
+        let laser_feed_frame = LaserFeedFrame {
+            source_key,
+            feed_index,
+            frame_sequence,
+            point_count,
+            points: Cow::Owned(points),
+        };
+        Ok(laser_feed_frame)
+    }
+}
+
+impl ReadFromBytes for LaserPoint {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let x_low_byte = reader.read_u8()?;
+        let y_low_byte = reader.read_u8()?;
+        let xy_high_nibbles = reader.read_u8()?;
+        let color = reader.read_u16::<LE>()?;
+        let laser_point = LaserPoint {
+            x_low_byte,
+            y_low_byte,
+            xy_high_nibbles,
+            color,
+        };
+        Ok(laser_point)
+    }
+}
+
 impl ReadFromBytes for EnterShow {
-    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
         let name = Ucs2::read_from_bytes(reader)?;
         Ok(EnterShow { name })
     }
 }
 
+impl ReadFromBytes for LeaveShow {
+    fn read_from_bytes<R: ReadBytesExt>(_reader: R) -> Result<Self> {
+        Ok(LeaveShow {})
+    }
+}
+
+impl ReadFromBytes for FixtureListRequest {
+    fn read_from_bytes<R: ReadBytesExt>(_reader: R) -> Result<Self> {
+        Ok(FixtureListRequest {})
+    }
+}
+
 impl<'a> ReadFromBytes for FixtureList<'a> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
-        let message_type: FixtureListMessageType = reader.read_u8()?.into();
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let message_type = reader.read_u8()?.try_into()?;
         let fixture_count = reader.read_u16::<LE>()?;
         let mut fixtures = Vec::new();
         for _ in 0..fixture_count {
@@ -588,11 +843,10 @@ impl<'a> ReadFromBytes for FixtureList<'a> {
 }
 
 impl<'a> ReadFromBytes for Identifier<'a> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
-        let identifier_type: IdentifierType = reader.read_u8()?.into();
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let identifier_type = reader.read_u8()?.try_into()?;
         let data_size = reader.read_u16::<LE>()?;
-        let mut data = vec![0u8; data_size.into()];
-        reader.read_exact(&mut data)?;
+        let data = protocol::read_new_bytes(reader, data_size as _)?;
         Ok(Identifier {
             identifier_type,
             data_size,
@@ -602,7 +856,7 @@ impl<'a> ReadFromBytes for Identifier<'a> {
 }
 
 impl ReadFromBytes for FixtureData {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         Ok(FixtureData {
             patched: reader.read_u8()?,
             universe: reader.read_u8()?,
@@ -626,7 +880,7 @@ impl ReadFromBytes for FixtureData {
 }
 
 impl<'a> ReadFromBytes for FixtureRemove<'a> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_count = reader.read_u16::<LE>()?;
         let mut fixture_identifiers = Vec::new();
         for _ in 0..fixture_count {
@@ -639,12 +893,69 @@ impl<'a> ReadFromBytes for FixtureRemove<'a> {
     }
 }
 
+impl ReadFromBytes for FixtureConsoleStatus<'static> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let fixture_count = reader.read_u16::<LE>()?;
+        let fixtures_state = protocol::read_new_vec(reader, fixture_count as _)?;
+        Ok(FixtureConsoleStatus {
+            fixture_count,
+            fixtures_state: Cow::Owned(fixtures_state),
+        })
+    }
+}
+
+impl ReadFromBytes for FixtureState {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let fixture_identifier = reader.read_u32::<LE>()?;
+        let locked = reader.read_u8()?;
+        let clearable = reader.read_u8()?;
+        Ok(FixtureState {
+            fixture_identifier,
+            locked,
+            clearable,
+        })
+    }
+}
+
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.caex_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
+impl SizeBytes for Nack {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u8>()
+    }
+}
+
 impl SizeBytes for EnterShow {
     fn size_bytes(&self) -> usize {
         self.name.size_bytes()
     }
 }
 
+impl SizeBytes for LeaveShow {
+    fn size_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl SizeBytes for FixtureListRequest {
+    fn size_bytes(&self) -> usize {
+        0
+    }
+}
+
 impl<'a> SizeBytes for FixtureList<'a> {
     fn size_bytes(&self) -> usize {
         let mut fixtures_size = 0;
@@ -709,6 +1020,28 @@ impl<'a> SizeBytes for FixtureRemove<'a> {
     }
 }
 
+impl<'a> SizeBytes for FixtureConsoleStatus<'a> {
+    fn size_bytes(&self) -> usize {
+        let mut fixtures_state_size = 0;
+        for state in self.fixtures_state.iter() {
+            fixtures_state_size += state.size_bytes();
+        }
+        mem::size_of::<u16>() + fixtures_state_size
+    }
+}
+
+impl SizeBytes for FixtureState {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u32>() + mem::size_of::<u8>() + mem::size_of::<u8>()
+    }
+}
+
+impl SizeBytes for GetLaserFeedList {
+    fn size_bytes(&self) -> usize {
+        0
+    }
+}
+
 impl<'a> SizeBytes for LaserFeedList<'a> {
     fn size_bytes(&self) -> usize {
         let mut feed_names_size = 0;
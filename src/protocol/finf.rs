@@ -1,10 +1,11 @@
 use crate::protocol::{
-    self, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt,
-    WriteToBytes, LE,
+    self, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes,
+    WriteBytesExt, WriteToBytes, LE,
 };
+use crate::Result;
 use std::borrow::Cow;
 use std::ffi::CString;
-use std::{io, mem};
+use std::mem;
 
 /// The FINF layer provides a standard, single, header used at the start of all FINF packets.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -56,6 +57,26 @@ impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"FINF";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a FINF message, filling in the CITP and FINF headers.
+    pub fn new(message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let finf_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            finf_header,
+            message,
+        };
+        msg.finf_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl<'a> SFra<'a> {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"SFra";
 }
@@ -64,8 +85,20 @@ impl Fram {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"Fram";
 }
 
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for SFra<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for Fram {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         Ok(())
@@ -76,7 +109,7 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.finf_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
@@ -84,7 +117,7 @@ where
 }
 
 impl<'a> WriteToBytes for SFra<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifiers.len() as _)?;
         for &id in self.fixture_identifiers.iter() {
             writer.write_u16::<LE>(id)?;
@@ -94,7 +127,7 @@ impl<'a> WriteToBytes for SFra<'a> {
 }
 
 impl WriteToBytes for Fram {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifier)?;
         writer.write_u8(self.frame_filter_count)?;
         writer.write_u8(self.frame_gobo_count)?;
@@ -103,8 +136,39 @@ impl WriteToBytes for Fram {
     }
 }
 
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let finf_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, finf_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&finf_header.citp_header, reader.count())?;
+        let msg = Message {
+            finf_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
 impl ReadFromBytes for SFra<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_count = reader.read_u16::<LE>()?;
         let fixture_identifiers = protocol::read_new_vec(reader, fixture_count as _)?;
         let fixture_identifiers = Cow::Owned(fixture_identifiers);
@@ -116,7 +180,7 @@ impl ReadFromBytes for SFra<'static> {
 }
 
 impl ReadFromBytes for Fram {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_identifier = reader.read_u16::<LE>()?;
         let frame_filter_count = reader.read_u8()?;
         let frame_gobo_count = reader.read_u8()?;
@@ -131,6 +195,21 @@ impl ReadFromBytes for Fram {
     }
 }
 
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.finf_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
 impl<'a> SizeBytes for SFra<'a> {
     fn size_bytes(&self) -> usize {
         mem::size_of::<u16>() + self.fixture_identifiers.len() * mem::size_of::<u16>()
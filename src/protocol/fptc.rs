@@ -1,10 +1,11 @@
 use crate::protocol::{
-    self, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt,
-    WriteToBytes, LE,
+    self, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes,
+    WriteBytesExt, WriteToBytes, LE,
 };
+use crate::Result;
 use std::borrow::Cow;
 use std::ffi::CString;
-use std::{io, mem};
+use std::mem;
 
 /// The FPTC layer provides a standard, single, header used at the start of all FPTC packets.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -83,6 +84,28 @@ impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"FPTC";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a FPTC message with the given content hint, filling in the CITP and FPTC
+    /// headers.
+    pub fn new(message: T, content_hint: u32) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let fptc_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+            content_hint,
+        };
+        let mut msg = Message {
+            fptc_header,
+            message,
+        };
+        msg.fptc_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl Ptch {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"Ptch";
 }
@@ -95,8 +118,24 @@ impl<'a> SPtc<'a> {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"SPtc";
 }
 
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for Ptch {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for UPtc<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for SPtc<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         writer.write_u32::<LE>(self.content_hint)?;
@@ -108,7 +147,7 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.fptc_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
@@ -116,7 +155,7 @@ where
 }
 
 impl WriteToBytes for Ptch {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifier)?;
         writer.write_u8(self.universe)?;
         writer.write_u8(self.reserved)?;
@@ -129,7 +168,7 @@ impl WriteToBytes for Ptch {
 }
 
 impl<'a> WriteToBytes for UPtc<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifiers.len() as _)?;
         for &id in self.fixture_identifiers.iter() {
             writer.write_u16::<LE>(id)?;
@@ -139,7 +178,7 @@ impl<'a> WriteToBytes for UPtc<'a> {
 }
 
 impl<'a> WriteToBytes for SPtc<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifiers.len() as _)?;
         for &id in self.fixture_identifiers.iter() {
             writer.write_u16::<LE>(id)?;
@@ -148,8 +187,41 @@ impl<'a> WriteToBytes for SPtc<'a> {
     }
 }
 
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let content_hint = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+            content_hint,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let fptc_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, fptc_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&fptc_header.citp_header, reader.count())?;
+        let msg = Message {
+            fptc_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
 impl ReadFromBytes for Ptch {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_identifier = reader.read_u16::<LE>()?;
         let universe = reader.read_u8()?;
         let reserved = reader.read_u8()?;
@@ -171,7 +243,7 @@ impl ReadFromBytes for Ptch {
 }
 
 impl ReadFromBytes for UPtc<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_count: u16 = reader.read_bytes()?;
         let fixture_identifiers = protocol::read_new_vec(reader, fixture_count as _)?;
         let fixture_identifiers = Cow::Owned(fixture_identifiers);
@@ -183,7 +255,7 @@ impl ReadFromBytes for UPtc<'static> {
 }
 
 impl ReadFromBytes for SPtc<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_count: u16 = reader.read_bytes()?;
         let fixture_identifiers = protocol::read_new_vec(reader, fixture_count as _)?;
         let fixture_identifiers = Cow::Owned(fixture_identifiers);
@@ -194,6 +266,21 @@ impl ReadFromBytes for SPtc<'static> {
     }
 }
 
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.fptc_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
 impl SizeBytes for Ptch {
     fn size_bytes(&self) -> usize {
         mem::size_of::<u16>()
@@ -208,12 +295,12 @@ impl SizeBytes for Ptch {
 
 impl<'a> SizeBytes for UPtc<'a> {
     fn size_bytes(&self) -> usize {
-        self.fixture_identifiers.len() * mem::size_of::<u16>()
+        mem::size_of::<u16>() + self.fixture_identifiers.len() * mem::size_of::<u16>()
     }
 }
 
 impl<'a> SizeBytes for SPtc<'a> {
     fn size_bytes(&self) -> usize {
-        self.fixture_identifiers.len() * mem::size_of::<u16>()
+        mem::size_of::<u16>() + self.fixture_identifiers.len() * mem::size_of::<u16>()
     }
 }
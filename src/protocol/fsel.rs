@@ -1,8 +1,10 @@
 use crate::protocol::{
-    self, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt, WriteToBytes, LE,
+    self, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes,
+    WriteBytesExt, WriteToBytes, LE,
 };
+use crate::Result;
 use std::borrow::Cow;
-use std::{io, mem};
+use std::mem;
 
 /// The FSEL layer provides a standard, single, header used at the start of all FSEL packets.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
@@ -56,6 +58,26 @@ impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"FSEL";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a FSEL message, filling in the CITP and FSEL headers.
+    pub fn new(message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let fsel_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            fsel_header,
+            message,
+        };
+        msg.fsel_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl<'a> Sele<'a> {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"Sele";
 }
@@ -64,8 +86,20 @@ impl<'a> DeSe<'a> {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"DeSe";
 }
 
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for Sele<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for DeSe<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         Ok(())
@@ -76,7 +110,7 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.fsel_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
@@ -84,7 +118,7 @@ where
 }
 
 impl<'a> WriteToBytes for Sele<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.complete)?;
         writer.write_u8(self.reserved)?;
         writer.write_u16::<LE>(self.fixture_identifiers.len() as _)?;
@@ -96,7 +130,7 @@ impl<'a> WriteToBytes for Sele<'a> {
 }
 
 impl<'a> WriteToBytes for DeSe<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.fixture_identifiers.len() as _)?;
         for &id in self.fixture_identifiers.iter() {
             writer.write_u16::<LE>(id)?;
@@ -105,8 +139,39 @@ impl<'a> WriteToBytes for DeSe<'a> {
     }
 }
 
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let fsel_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, fsel_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&fsel_header.citp_header, reader.count())?;
+        let msg = Message {
+            fsel_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
 impl ReadFromBytes for Sele<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let complete = reader.read_u8()?;
         let reserved = reader.read_u8()?;
         let fixture_count = reader.read_u16::<LE>()?;
@@ -122,7 +187,7 @@ impl ReadFromBytes for Sele<'static> {
 }
 
 impl ReadFromBytes for DeSe<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let fixture_count = reader.read_u16::<LE>()?;
         let fixture_identifiers = protocol::read_new_vec(reader, fixture_count as _)?;
         let fixture_identifiers = Cow::Owned(fixture_identifiers);
@@ -133,6 +198,21 @@ impl ReadFromBytes for DeSe<'static> {
     }
 }
 
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.fsel_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
 impl<'a> SizeBytes for Sele<'a> {
     fn size_bytes(&self) -> usize {
         mem::size_of::<u8>()
@@ -27,13 +27,19 @@
 //! - Match on the `content_type` field to determine the next layer to read.
 //! - Read the header for the second layer.
 //! - Match on the `content_type` field of the second layer to determine what type to read.
+//!
+//! Alternatively, **Packet::decode** performs all of these steps for any message defined within
+//! this crate.
 
+use crate::{Error, Result};
 pub use byteorder::{ReadBytesExt, WriteBytesExt, LE};
 use std::{
     ffi::CString,
     fmt,
     hash::{Hash, Hasher},
-    io, mem,
+    io::{self, Read},
+    mem,
+    num::NonZeroU16,
     str::FromStr,
 };
 
@@ -52,8 +58,8 @@ use std::{
 /// immediately be sent as the first message.
 pub mod pinf;
 
-/// The SDMX layer is used to transmit DMX information. CITP supports transmitting a single - wide
-/// - universe of DMX channels with at most 65_536 channels. It also supports designating an
+/// The SDMX layer is used to transmit DMX information. CITP supports transmitting a single - wide -
+/// universe of DMX channels with at most 65_536 channels. It also supports designating an
 /// alternative DMX source such as ArtNet or ETCNet2 (see "connection strings").
 pub mod sdmx;
 
@@ -163,7 +169,7 @@ pub mod finf;
 /// frames:
 ///
 /// - RGB8 - a raw array of 8-byte RGB triples (this is **not** BMP). In MSEX 1.0 the byte order
-/// was BGR, but from MSEX 1.1 the byte order is RGB.
+///   was BGR, but from MSEX 1.1 the byte order is RGB.
 /// - JPEG - the well known file format (which does **not** include EXIF).
 /// - PNG - the well known file format. Requires MSEX 1.2.
 /// - Fragmented JPB - JPEG data fragments (for streams only). Requires MSEX 1.2.
@@ -176,30 +182,44 @@ pub mod msex;
 /// as a private CITP (http://www.citp-protocol.org) layer.
 pub mod caex;
 
+mod packet;
+
+pub use self::packet::{
+    CaexMessage, FinfMessage, FptcMessage, FselMessage, MsexMessage, Packet, PinfMessage,
+    SdmxMessage,
+};
+
 /// A trait for writing any of the CITP protocol types to little-endian bytes.
 ///
 /// A blanket implementation is provided for all types that implement `byteorder::WriteBytesExt`.
 pub trait WriteBytes {
-    fn write_bytes<P: WriteToBytes>(&mut self, protocol: P) -> io::Result<()>;
+    fn write_bytes<P: WriteToBytes>(&mut self, protocol: P) -> Result<()>;
 }
 
 /// A trait for reading any of the CITP protocol types from little-endian bytes.
 ///
 /// A blanket implementation is provided for all types that implement `byteorder::ReadBytesExt`.
+///
+/// If the reader is exhausted before the type has been read, a `Truncated` error is returned in
+/// which `actual` is the number of bytes that were available and `expected` is the number of bytes
+/// required by the fields read so far.
 pub trait ReadBytes {
-    fn read_bytes<P: ReadFromBytes>(&mut self) -> io::Result<P>;
+    fn read_bytes<P: ReadFromBytes>(&mut self) -> Result<P>;
 }
 
 /// Protocol types that may be written to little endian bytes.
 pub trait WriteToBytes {
     /// Write the command to bytes.
-    fn write_to_bytes<W: WriteBytesExt>(&self, writer: W) -> io::Result<()>;
+    fn write_to_bytes<W: WriteBytesExt>(&self, writer: W) -> Result<()>;
 }
 
 /// Protocol types that may be read from little endian bytes.
+///
+/// Prefer reading via `ReadBytes::read_bytes`, which reports an exhausted reader as a `Truncated`
+/// error rather than an `Io` error.
 pub trait ReadFromBytes: Sized {
     /// Read the command from bytes.
-    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> io::Result<Self>;
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self>;
 }
 
 /// Types that have a constant size when written to or read from bytes.
@@ -212,6 +232,23 @@ pub trait SizeBytes {
     fn size_bytes(&self) -> usize;
 }
 
+/// Message types and layer headers that are identified by a content type cookie.
+///
+/// For messages, this is the cookie written to the `content_type` field of the layer header. For
+/// layer headers, this is the cookie written to the `content_type` field of the base header.
+pub trait ContentType {
+    /// The cookie identifying the layer or message.
+    const COOKIE: Cookie;
+}
+
+/// A four byte cookie identifying a layer or message type, e.g. "PINF" or "PLoc".
+///
+/// Most cookies are four ASCII characters, read and written as a little-endian `u32`. CAEX uses
+/// numeric cookies instead. Cookies are formatted as their four characters where they are
+/// printable ASCII, otherwise as hexadecimal.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Cookie(pub u32);
+
 /// The CITP layer provides a standard, single, header used at the start of all CITP packets.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
@@ -242,6 +279,34 @@ pub struct Header {
     pub content_type: u32,
 }
 
+impl Header {
+    pub const COOKIE: &'static [u8; 4] = b"CITP";
+    pub const VERSION_MAJOR: u8 = 1;
+    pub const VERSION_MINOR: u8 = 0;
+
+    /// Construct the header for a single, unfragmented message.
+    ///
+    /// The `content_type` is the cookie of the second layer and `message_size` is the size of the
+    /// entire message, including this header.
+    pub fn new(content_type: u32, message_size: u32) -> Self {
+        Header {
+            cookie: u32::from_le_bytes(*Self::COOKIE),
+            version_major: Self::VERSION_MAJOR,
+            version_minor: Self::VERSION_MINOR,
+            kind: Kind::IGNORED,
+            message_size,
+            message_part_count: 1,
+            message_part: 0,
+            content_type,
+        }
+    }
+}
+
+/// The request index of a request message or, for response messages, the request index of the
+/// message being responded to.
+///
+/// Both fields share the same `u16`. The safe methods below may be used to read and construct
+/// the value without `unsafe` blocks.
 #[derive(Copy, Clone)]
 #[repr(C)]
 pub union Kind {
@@ -249,14 +314,109 @@ pub union Kind {
     pub in_response_to: u16,
 }
 
+/// A valid, non-zero request index.
+///
+/// The value `0` within the base header means the field is ignored and is represented as `None`
+/// by the `Kind` accessors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct RequestIndex(NonZeroU16);
+
+/// Allocates request indices for outgoing request messages.
+///
+/// Indices start at `1`, increment with every request and wrap back around to `1`, avoiding the
+/// `0` "ignored" value.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RequestCounter {
+    last: Option<RequestIndex>,
+}
+
+impl Kind {
+    /// The value used by messages that are neither requests nor responses.
+    pub const IGNORED: Self = Kind { request_index: 0 };
+
+    /// A `Kind` identifying a request message.
+    pub fn request(index: RequestIndex) -> Self {
+        Kind {
+            request_index: index.get(),
+        }
+    }
+
+    /// A `Kind` identifying a response to the request with the given index.
+    pub fn response(in_response_to: RequestIndex) -> Self {
+        Kind {
+            in_response_to: in_response_to.get(),
+        }
+    }
+
+    /// The raw value of the field.
+    pub fn get(&self) -> u16 {
+        // Both fields of the union are a `u16` so either is always valid to read.
+        unsafe { self.request_index }
+    }
+
+    /// The request index, or `None` if the field is ignored.
+    pub fn request_index(&self) -> Option<RequestIndex> {
+        RequestIndex::new(self.get())
+    }
+
+    /// The request index being responded to, or `None` if the field is ignored.
+    pub fn in_response_to(&self) -> Option<RequestIndex> {
+        RequestIndex::new(self.get())
+    }
+}
+
+impl RequestIndex {
+    /// The first index used by a new `RequestCounter`.
+    pub const FIRST: Self = RequestIndex(NonZeroU16::MIN);
+
+    /// Returns `None` for the `0` "ignored" value.
+    pub fn new(index: u16) -> Option<Self> {
+        NonZeroU16::new(index).map(RequestIndex)
+    }
+
+    /// The index as a `u16`.
+    pub fn get(self) -> u16 {
+        self.0.get()
+    }
+
+    /// The index following this one, wrapping from `u16::MAX` back around to `1`.
+    pub fn next(self) -> Self {
+        RequestIndex::new(self.get().wrapping_add(1)).unwrap_or(Self::FIRST)
+    }
+}
+
+impl RequestCounter {
+    /// A counter whose first index will be `1`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate the next request index.
+    pub fn next_index(&mut self) -> RequestIndex {
+        let next = match self.last {
+            None => RequestIndex::FIRST,
+            Some(last) => last.next(),
+        };
+        self.last = Some(next);
+        next
+    }
+}
+
+impl From<RequestIndex> for u16 {
+    fn from(index: RequestIndex) -> Self {
+        index.get()
+    }
+}
+
 impl WriteToBytes for Kind {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
-        unsafe { writer.write_u16::<LE>(self.request_index) }
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u16::<LE>(self.get())?;
+        Ok(())
     }
 }
 
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u32::<LE>(self.cookie)?;
         writer.write_u8(self.version_major)?;
         writer.write_u8(self.version_minor)?;
@@ -270,17 +430,28 @@ impl WriteToBytes for Header {
 }
 
 impl ReadFromBytes for Kind {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let request_index = reader.read_u16::<LE>()?;
         Ok(Kind { request_index })
     }
 }
 
 impl ReadFromBytes for Header {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let cookie = reader.read_u32::<LE>()?;
+        if cookie != u32::from_le_bytes(*Self::COOKIE) {
+            return Err(Error::BadCookie {
+                found: Cookie(cookie),
+            });
+        }
         let version_major = reader.read_u8()?;
         let version_minor = reader.read_u8()?;
+        if version_major != Self::VERSION_MAJOR {
+            return Err(Error::UnsupportedVersion {
+                major: version_major,
+                minor: version_minor,
+            });
+        }
         let kind = reader.read_bytes()?;
         let message_size = reader.read_u32::<LE>()?;
         let message_part_count = reader.read_u16::<LE>()?;
@@ -304,7 +475,7 @@ impl<W> WriteBytes for W
 where
     W: WriteBytesExt,
 {
-    fn write_bytes<P: WriteToBytes>(&mut self, protocol: P) -> io::Result<()> {
+    fn write_bytes<P: WriteToBytes>(&mut self, protocol: P) -> Result<()> {
         protocol.write_to_bytes(self)
     }
 }
@@ -313,22 +484,30 @@ impl<R> ReadBytes for R
 where
     R: ReadBytesExt,
 {
-    fn read_bytes<P: ReadFromBytes>(&mut self) -> io::Result<P> {
-        P::read_from_bytes(self)
+    fn read_bytes<P: ReadFromBytes>(&mut self) -> Result<P> {
+        let mut reader = CountingReader::new(self);
+        P::read_from_bytes(&mut reader).map_err(|err| match err {
+            // Describe truncation relative to the start of `P` rather than the nested field.
+            Error::Truncated { expected, actual } => Error::Truncated {
+                expected: reader.count() + expected.saturating_sub(actual),
+                actual: reader.count(),
+            },
+            err => err,
+        })
     }
 }
 
-impl<'a, T> WriteToBytes for &'a T
+impl<T> WriteToBytes for &T
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, writer: W) -> Result<()> {
         (**self).write_to_bytes(writer)
     }
 }
 
 impl WriteToBytes for CString {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         let bytes = self.as_bytes_with_nul();
         for &byte in bytes {
             writer.write_u8(byte)?;
@@ -338,7 +517,7 @@ impl WriteToBytes for CString {
 }
 
 impl ReadFromBytes for CString {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let mut bytes = vec![];
         loop {
             match reader.read_u8()? {
@@ -352,14 +531,14 @@ impl ReadFromBytes for CString {
 }
 
 impl ReadFromBytes for u8 {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
-        reader.read_u8()
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        Ok(reader.read_u8()?)
     }
 }
 
 impl ReadFromBytes for u16 {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
-        reader.read_u16::<LE>()
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        Ok(reader.read_u16::<LE>()?)
     }
 }
 
@@ -383,7 +562,60 @@ impl SizeBytes for Header {
 
 impl fmt::Debug for Kind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        unsafe { write!(f, "{:?}", self.request_index) }
+        write!(f, "{:?}", self.get())
+    }
+}
+
+impl Cookie {
+    /// The cookie whose little-endian bytes are the given characters.
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+        Cookie(u32::from_le_bytes(bytes))
+    }
+
+    /// The little-endian bytes of the cookie.
+    pub const fn to_bytes(self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+
+    /// The cookie as read from or written to a `content_type` field.
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<u32> for Cookie {
+    fn from(cookie: u32) -> Self {
+        Cookie(cookie)
+    }
+}
+
+impl From<[u8; 4]> for Cookie {
+    fn from(bytes: [u8; 4]) -> Self {
+        Cookie::from_bytes(bytes)
+    }
+}
+
+impl From<Cookie> for u32 {
+    fn from(cookie: Cookie) -> Self {
+        cookie.0
+    }
+}
+
+impl fmt::Display for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bytes = self.to_bytes();
+        if bytes.iter().all(|&b| b.is_ascii_graphic() || b == b' ') {
+            let chars: String = bytes.iter().map(|&b| b as char).collect();
+            write!(f, "{}", chars)
+        } else {
+            write!(f, "{:#010x}", self.0)
+        }
+    }
+}
+
+impl fmt::Debug for Cookie {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Cookie({})", self)
     }
 }
 
@@ -391,20 +623,18 @@ impl Eq for Kind {}
 
 impl PartialEq for Kind {
     fn eq(&self, other: &Self) -> bool {
-        unsafe { self.request_index == other.request_index }
+        self.get() == other.get()
     }
 }
 
 impl Hash for Kind {
     fn hash<H: Hasher>(&self, state: &mut H) {
-        unsafe {
-            self.request_index.hash(state);
-        }
+        self.get().hash(state);
     }
 }
 
 /// Read **len** elements of type **T** into the given **vec**.
-pub fn read_vec<R, T>(mut reader: R, mut len: usize, vec: &mut Vec<T>) -> io::Result<()>
+pub fn read_vec<R, T>(mut reader: R, mut len: usize, vec: &mut Vec<T>) -> Result<()>
 where
     R: ReadBytesExt,
     T: ReadFromBytes,
@@ -418,7 +648,7 @@ where
 }
 
 /// Read **len** elements of type **T** into a new **Vec**.
-pub fn read_new_vec<R, T>(reader: R, len: usize) -> io::Result<Vec<T>>
+pub fn read_new_vec<R, T>(reader: R, len: usize) -> Result<Vec<T>>
 where
     R: ReadBytesExt,
     T: ReadFromBytes,
@@ -428,29 +658,123 @@ where
     Ok(vec)
 }
 
-#[derive(Clone, PartialEq, Eq, Hash)]
-pub struct Ucs2(Vec<u16>);
+/// Read exactly **len** bytes into a new **Vec**.
+///
+/// Returns a `Truncated` error if the reader is exhausted before **len** bytes are read.
+pub fn read_new_bytes<R>(reader: R, len: usize) -> Result<Vec<u8>>
+where
+    R: ReadBytesExt,
+{
+    let mut bytes = Vec::with_capacity(len);
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() < len {
+        return Err(Error::Truncated {
+            expected: len,
+            actual: bytes.len(),
+        });
+    }
+    Ok(bytes)
+}
 
-impl Ucs2 {
-    /// Read ucs2 bytes until [0,0] is found
-    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> io::Result<Self> {
-        let mut ucs2: Ucs2 = Ucs2(Vec::new());
-        let mut bytes = reader.bytes();
-        while let Some(curr) = bytes.next() {
-            if let Some(next) = bytes.next() {
-                let val = [curr?, next?];
-                let y = u16::from_le_bytes(val);
-                if y == 0 {
-                    break;
-                } else {
-                    ucs2.0.push(y);
+/// Produce a `ContentTypeMismatch` error if the **found** cookie is not the **expected** cookie.
+pub(crate) fn check_content_type(expected: Cookie, found: u32) -> Result<()> {
+    if expected.get() != found {
+        return Err(Error::ContentTypeMismatch {
+            expected,
+            found: Cookie(found),
+        });
+    }
+    Ok(())
+}
+
+/// Produce a `MessageSizeMismatch` error if the `message_size` of the given base header does not
+/// match the **actual** number of bytes read.
+pub(crate) fn check_message_size(citp_header: &Header, actual: usize) -> Result<()> {
+    let message_size = citp_header.message_size as usize;
+    if message_size != actual {
+        return Err(Error::MessageSizeMismatch {
+            message_size,
+            actual,
+        });
+    }
+    Ok(())
+}
+
+/// A reader that counts the bytes read from the inner reader.
+///
+/// Used to validate the `message_size` of the base header against the bytes consumed by a message,
+/// and to produce a `Truncated` error describing the read that exhausted the inner reader.
+pub(crate) struct CountingReader<R> {
+    reader: R,
+    count: usize,
+}
+
+impl<R> CountingReader<R> {
+    pub(crate) fn new(reader: R) -> Self {
+        CountingReader { reader, count: 0 }
+    }
+
+    /// The number of bytes read so far.
+    pub(crate) fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(buf)?;
+        self.count += n;
+        Ok(n)
+    }
+
+    fn read_exact(&mut self, mut buf: &mut [u8]) -> io::Result<()> {
+        let expected = self.count + buf.len();
+        while !buf.is_empty() {
+            match self.read(buf) {
+                Ok(0) => {
+                    let err = Error::Truncated {
+                        expected,
+                        actual: self.count,
+                    };
+                    return Err(io::Error::new(io::ErrorKind::UnexpectedEof, err));
                 }
+                Ok(n) => buf = &mut buf[n..],
+                Err(ref err) if err.kind() == io::ErrorKind::Interrupted => (),
+                Err(err) => return Err(err),
             }
         }
-        Ok(ucs2)
+        Ok(())
+    }
+}
+
+/// Produce a `CountMismatch` error if the given **count** field does not match **actual**.
+pub(crate) fn check_count(field: &'static str, count: usize, actual: usize) -> Result<()> {
+    if count != actual {
+        return Err(Error::CountMismatch {
+            field,
+            count,
+            actual,
+        });
     }
+    Ok(())
+}
+
+#[derive(Clone, Default, PartialEq, Eq, Hash)]
+pub struct Ucs2(Vec<u16>);
+
+impl Ucs2 {
+    pub fn to_string(&self) -> Result<String> {
+        // Each UCS-2 code point requires at most three bytes of UTF-8.
+        let mut utf8_buf = vec![0u8; self.0.len() * 3];
+        let len = ucs2::decode(&self.0, &mut utf8_buf)?;
+        utf8_buf.truncate(len);
+        let name = String::from_utf8(utf8_buf).expect("`ucs2::decode` produced invalid UTF-8");
+        Ok(name)
+    }
+}
 
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+impl WriteToBytes for Ucs2 {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         for n in &self.0 {
             writer.write_u16::<LE>(*n)?;
         }
@@ -458,20 +782,28 @@ impl Ucs2 {
         writer.write_u16::<LE>(0)?;
         Ok(())
     }
+}
 
-    pub fn to_string(&self) -> Result<String, ucs2::Error> {
-        let mut utf8_buf = vec![0u8; self.0.len()];
-        ucs2::decode(&self.0, &mut utf8_buf)?;
-        let name = std::str::from_utf8(&utf8_buf).unwrap().clone().to_string();
-        Ok(name)
+impl ReadFromBytes for Ucs2 {
+    /// Read ucs2 bytes until [0,0] is found
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let mut ucs2: Ucs2 = Ucs2(Vec::new());
+        loop {
+            match reader.read_u16::<LE>()? {
+                0 => break,
+                y => ucs2.0.push(y),
+            }
+        }
+        Ok(ucs2)
     }
 }
 
 impl FromStr for Ucs2 {
-    type Err = ucs2::Error;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
+    type Err = Error;
+    fn from_str(s: &str) -> Result<Self> {
         let mut ucs2_buf = vec![0u16; s.len()];
-        ucs2::encode(s, &mut ucs2_buf)?;
+        let len = ucs2::encode(s, &mut ucs2_buf)?;
+        ucs2_buf.truncate(len);
         Ok(Ucs2(ucs2_buf))
     }
 }
@@ -1,5 +1,18 @@
-use crate::protocol;
+use crate::protocol::{
+    self, ConstSizeBytes, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes,
+    Ucs2, WriteBytes, WriteBytesExt, WriteToBytes, LE,
+};
+use crate::{Error, Result};
 use std::borrow::Cow;
+use std::ffi::CString;
+use std::{fmt, mem};
+
+/// A model of the state of a media server, maintained from the messages it sends.
+pub mod client;
+/// A mock media server for testing clients without a real media server.
+pub mod server;
+/// Reassembly of video frames received via StFr messages.
+pub mod stream;
 
 /// The MSEX layer provides a standard, single, header used at the start of all MSEX packets.
 ///
@@ -18,6 +31,43 @@ pub struct Header {
     pub content_type: u32,
 }
 
+/// An MSEX version, as carried by the MSEX header.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+/// A universally unique identifier, carried as 36 ASCII characters,
+/// e.g. "0e4fd6a4-39c3-4a7f-9c6e-dbbd5ec8b34d".
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Uuid(pub [u8; 36]);
+
+/// Writing of MSEX messages whose layout depends on the MSEX version in use.
+///
+/// Implemented for all `WriteToBytes` types, whose layout is the same for every version.
+pub trait WriteToBytesVersioned {
+    /// Write the message to bytes using the layout of the given MSEX version.
+    fn write_to_bytes_versioned<W: WriteBytesExt>(&self, version: Version, writer: W)
+        -> Result<()>;
+}
+
+/// Reading of MSEX messages whose layout depends on the MSEX version in use.
+///
+/// Implemented for all `ReadFromBytes` types, whose layout is the same for every version.
+pub trait ReadFromBytesVersioned: Sized {
+    /// Read the message from bytes using the layout of the given MSEX version.
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, reader: R) -> Result<Self>;
+}
+
+/// The size of MSEX messages whose layout depends on the MSEX version in use.
+///
+/// Implemented for all `SizeBytes` types, whose layout is the same for every version.
+pub trait SizeBytesVersioned {
+    /// The size of the message in bytes when written using the given MSEX version.
+    fn size_bytes_versioned(&self, version: Version) -> usize;
+}
+
 /// Layout of MSEX messages.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 #[repr(C)]
@@ -49,3 +99,2978 @@ pub struct CInf<'a> {
     /// A hint that future versions of this message may contain trailing data.
     pub future_message_data: Cow<'a, [u8]>,
 }
+
+/// ## MSEX / SINF - Server Information message
+///
+/// The Server Information message provides the client with information about the media server.
+/// Prior to MSEX 1.2 the message is sent by the media server immediately after a client connects.
+/// From MSEX 1.2 the message is sent in response to a MSEX/CInf message, using the Highest Common
+/// MSEX version.
+///
+/// Fields marked as requiring MSEX 1.2 are neither written nor read for earlier versions. When read
+/// from an earlier version they are left as their default values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SInf<'a> {
+    /// Uniquely identifies the media server. Requires MSEX 1.2.
+    pub uuid: Uuid,
+    /// Display name of the product.
+    pub product_name: Ucs2,
+    /// Major version number of the product.
+    pub product_version_major: u8,
+    /// Minor version number of the product.
+    pub product_version_minor: u8,
+    /// Bugfix version number of the product. Requires MSEX 1.2.
+    pub product_version_bugfix: u8,
+    /// Number of following MSEX version pairs. Requires MSEX 1.2.
+    pub supported_msex_versions_count: u8,
+    /// Each 2 byte value is MSB = major MSEX version, LSB = minor MSEX version. Requires MSEX 1.2.
+    pub supported_msex_versions: Cow<'a, [[u8; 2]]>,
+    /// Bit-encoded flags where bit `n` indicates support for library type `n + 1`. Requires MSEX
+    /// 1.2.
+    pub supported_library_types: u16,
+    /// Number of following thumbnail format cookies. Requires MSEX 1.2.
+    pub thumbnail_formats_count: u8,
+    /// Supported thumbnail formats, e.g. "RGB8", "JPEG" or "PNG ". Requires MSEX 1.2.
+    pub thumbnail_formats: Cow<'a, [u32]>,
+    /// Number of following stream format cookies. Requires MSEX 1.2.
+    pub stream_formats_count: u8,
+    /// Supported stream formats, e.g. "RGB8", "JPEG", "PNG ", "fJPG" or "fPNG". Requires MSEX 1.2.
+    pub stream_formats: Cow<'a, [u32]>,
+    /// Number of following layer DMX sources.
+    pub layer_count: u8,
+    /// A DMX connection string for each layer, e.g. "ArtNet/0/0/1" or "BSRE1.31/1/1".
+    pub layer_dmx_sources: Cow<'a, [CString]>,
+}
+
+/// ## MSEX / Nack - Negative Acknowledge message
+///
+/// The Negative Acknowledge message is sent in response to a message that was not understood or
+/// could not be processed, e.g. due to an unexpected MSEX version. Requires MSEX 1.2.
+///
+/// The `in_response_to` field of the base header carries the request index of the refused
+/// message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Nack {
+    /// The MSEX content type cookie of the message that was refused.
+    pub received_content_type: u32,
+}
+
+/// Identifies a media library within a hierarchy of up to three levels.
+///
+/// At level `0` the identifier refers to the root. At level `1` only `level_1` is used, at level
+/// `2` `level_1` and `level_2` are used, etc.
+///
+/// MSEX 1.0 libraries are not nested and are identified by a single number instead. These are
+/// represented as level `1` identifiers, see `LibraryId::from_number`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(C)]
+pub struct LibraryId {
+    /// The depth of the library within the hierarchy, `0` to `3`.
+    pub level: u8,
+    pub level_1: u8,
+    pub level_2: u8,
+    pub level_3: u8,
+}
+
+/// ## MSEX / LSta - Layer Status message
+///
+/// The Layer Status message is sent by the media server at regular intervals (approximately four
+/// times per second) and describes the media playing on each of its layers.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LSta<'a> {
+    /// Number of following layer status entries.
+    pub layer_count: u8,
+    /// The status of each layer.
+    pub layers: Cow<'a, [LayerStatus]>,
+}
+
+/// The status of a single layer within an LSta message.
+///
+/// Fields marked as requiring MSEX 1.2 are neither written nor read for earlier versions.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LayerStatus {
+    /// `0`-based layer number, corresponding to the layers reported in the SInf message.
+    pub layer_number: u8,
+    /// Current physical video output index, `0`-based.
+    pub physical_output: u8,
+    /// The type of the media library. Requires MSEX 1.2, always `LibraryType::Media` for earlier
+    /// versions.
+    pub media_library_type: LibraryType,
+    /// The identifier of the media library. Prior to MSEX 1.2 only the library number is carried,
+    /// see `LibraryId::from_number`.
+    pub media_library_id: LibraryId,
+    /// The number of the media within the library.
+    pub media_number: u8,
+    /// The name of the media.
+    pub media_name: Ucs2,
+    /// The current frame number.
+    pub media_position: u32,
+    /// The total number of frames.
+    pub media_length: u32,
+    /// The resolution of the media in frames per second.
+    pub media_fps: u8,
+    /// Layer status flags:
+    /// - 0x0001 - Media playing.
+    /// - 0x0002 - Media playback reverse. Requires MSEX 1.2.
+    /// - 0x0004 - Media playback looping. Requires MSEX 1.2.
+    /// - 0x0008 - Media playback bouncing. Requires MSEX 1.2.
+    /// - 0x0010 - Media playback random. Requires MSEX 1.2.
+    /// - 0x0020 - Media paused. Requires MSEX 1.2.
+    pub layer_status_flags: u32,
+}
+
+/// The kinds of element libraries provided by a media server.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum LibraryType {
+    Media,
+    Effects,
+    Cues,
+    Crossfades,
+    Masks,
+    BlendPresets,
+    EffectPresets,
+    ImagePresets,
+    Meshes3D,
+    /// A type not described by the specification, preserved as it was received.
+    Other(u8),
+}
+
+/// ## MSEX / GELI - Get Element Library Information message
+///
+/// Sent by the client to request information about the element libraries of the given type that
+/// are children of the given parent library. The server responds with an ELIn message.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GELI<'a> {
+    /// The type of the requested libraries.
+    pub library_type: LibraryType,
+    /// The parent of the requested libraries. Requires MSEX 1.1.
+    pub library_parent_id: LibraryId,
+    /// Number of following library numbers. Written as a `u8` prior to MSEX 1.2. `0` requests all
+    /// libraries.
+    pub library_count: u16,
+    /// The numbers of the requested libraries within the parent library.
+    pub library_numbers: Cow<'a, [u8]>,
+}
+
+/// ## MSEX / ELIn - Element Library Information message
+///
+/// Sent by the server in response to a GELI message, describing each of the requested libraries.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ELIn<'a> {
+    /// The type of the described libraries.
+    pub library_type: LibraryType,
+    /// Number of following library information entries. Written as a `u8` prior to MSEX 1.2.
+    pub library_count: u16,
+    /// Information about each library.
+    pub libraries: Cow<'a, [LibraryInformation]>,
+}
+
+/// Information about a single element library within an ELIn message.
+///
+/// Fields marked as requiring a later MSEX version are neither written nor read for earlier
+/// versions. When read from an earlier version they are left as their default values.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct LibraryInformation {
+    /// The identifier of the library. Only the library number is carried prior to MSEX 1.1.
+    pub id: LibraryId,
+    /// Changes whenever the contents of the library change. Requires MSEX 1.2.
+    pub serial_number: u32,
+    /// The minimum DMX value used to select the library.
+    pub dmx_range_min: u8,
+    /// The maximum DMX value used to select the library.
+    pub dmx_range_max: u8,
+    /// The display name of the library.
+    pub name: Ucs2,
+    /// The number of sub-libraries. Requires MSEX 1.1. Written as a `u8` prior to MSEX 1.2.
+    pub library_count: u16,
+    /// The number of elements within the library. Written as a `u8` prior to MSEX 1.2.
+    pub element_count: u16,
+}
+
+/// ## MSEX / ELUp - Element Library Updated message
+///
+/// Sent by the server when the contents of a library change, allowing the client to request the
+/// updated information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ELUp {
+    /// The type of the updated library.
+    pub library_type: LibraryType,
+    /// The updated library. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// Update flags:
+    /// - 0x01 - Existing elements have been updated.
+    /// - 0x02 - Elements have been added or removed.
+    /// - 0x04 - Sub-libraries have been updated.
+    /// - 0x08 - Sub-libraries have been added or removed.
+    /// - 0x10 - All elements have been affected. Requires MSEX 1.2.
+    /// - 0x20 - All sub-libraries have been affected. Requires MSEX 1.2.
+    pub update_flags: u8,
+    /// A 256 bit set indicating the affected element numbers. Requires MSEX 1.2.
+    pub affected_elements: [u8; 32],
+    /// A 256 bit set indicating the affected sub-library numbers. Requires MSEX 1.2.
+    pub affected_libraries: [u8; 32],
+}
+
+/// ## MSEX / GEIn - Get Element Information message
+///
+/// Sent by the client to request information about elements within a library. The server responds
+/// with a MEIn, EEIn or GLEI message depending on the library type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GEIn<'a> {
+    /// The type of the library containing the elements.
+    pub library_type: LibraryType,
+    /// The library containing the elements. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// Number of following element numbers. Written as a `u8` prior to MSEX 1.2. `0` requests all
+    /// elements.
+    pub element_count: u16,
+    /// The numbers of the requested elements.
+    pub element_numbers: Cow<'a, [u8]>,
+}
+
+/// ## MSEX / MEIn - Media Element Information message
+///
+/// Sent by the server in response to a GEIn message for a media library.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MEIn<'a> {
+    /// The library containing the elements. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// Number of following media information entries. Written as a `u8` prior to MSEX 1.2.
+    pub element_count: u16,
+    /// Information about each media element.
+    pub elements: Cow<'a, [MediaInformation]>,
+}
+
+/// Information about a single media element within a MEIn message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MediaInformation {
+    /// The number of the element within the library.
+    pub number: u8,
+    /// Changes whenever the element changes. Requires MSEX 1.2.
+    pub serial_number: u32,
+    /// The minimum DMX value used to select the element.
+    pub dmx_range_min: u8,
+    /// The maximum DMX value used to select the element.
+    pub dmx_range_max: u8,
+    /// The display name of the media.
+    pub media_name: Ucs2,
+    /// The time at which the media was last modified, in seconds since 1970-01-01 00:00 UTC.
+    pub media_version_timestamp: u64,
+    /// The width of the media in pixels.
+    pub media_width: u16,
+    /// The height of the media in pixels.
+    pub media_height: u16,
+    /// The length of the media in frames.
+    pub media_length: u32,
+    /// The resolution of the media in frames per second.
+    pub media_fps: u8,
+}
+
+/// ## MSEX / EEIn - Effect Element Information message
+///
+/// Sent by the server in response to a GEIn message for an effects library.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EEIn<'a> {
+    /// The library containing the elements. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// Number of following effect information entries. Written as a `u8` prior to MSEX 1.2.
+    pub effect_count: u16,
+    /// Information about each effect element.
+    pub effects: Cow<'a, [EffectInformation<'a>]>,
+}
+
+/// Information about a single effect element within an EEIn message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EffectInformation<'a> {
+    /// The number of the element within the library.
+    pub element_number: u8,
+    /// Changes whenever the element changes. Requires MSEX 1.2.
+    pub serial_number: u32,
+    /// The minimum DMX value used to select the element.
+    pub dmx_range_min: u8,
+    /// The maximum DMX value used to select the element.
+    pub dmx_range_max: u8,
+    /// The display name of the effect.
+    pub effect_name: Ucs2,
+    /// Number of following effect parameter names.
+    pub effect_parameter_count: u8,
+    /// The display name of each parameter of the effect.
+    pub effect_parameter_names: Cow<'a, [Ucs2]>,
+}
+
+/// ## MSEX / GLEI - Generic Element Information message
+///
+/// Sent by the server in response to a GEIn message for any library that is neither a media nor
+/// an effects library. Requires MSEX 1.1.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GLEI<'a> {
+    /// The type of the library containing the elements.
+    pub library_type: LibraryType,
+    /// The library containing the elements.
+    pub library_id: LibraryId,
+    /// Number of following element information entries. Written as a `u8` prior to MSEX 1.2.
+    pub element_count: u16,
+    /// Information about each element.
+    pub elements: Cow<'a, [GenericInformation]>,
+}
+
+/// Information about a single element within a GLEI message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GenericInformation {
+    /// The number of the element within the library.
+    pub element_number: u8,
+    /// Changes whenever the element changes. Requires MSEX 1.2.
+    pub serial_number: u32,
+    /// The minimum DMX value used to select the element.
+    pub dmx_range_min: u8,
+    /// The maximum DMX value used to select the element.
+    pub dmx_range_max: u8,
+    /// The display name of the element.
+    pub name: Ucs2,
+    /// The time at which the element was last modified, in seconds since 1970-01-01 00:00 UTC.
+    pub version_timestamp: u64,
+}
+
+/// The image formats in which thumbnails may be requested.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ThumbnailFormat {
+    /// A raw array of 8-bit RGB triples. The byte order is BGR in MSEX 1.0, see `bgr_to_rgb`.
+    Rgb8,
+    /// A JPEG file.
+    Jpeg,
+    /// A PNG file. Requires MSEX 1.2.
+    Png,
+}
+
+/// ## MSEX / GETh - Get Element Thumbnail message
+///
+/// Sent by the client to request thumbnails of elements within a library. The server responds
+/// with an EThn message for each element.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GETh<'a> {
+    /// The requested format of the thumbnails.
+    pub thumbnail_format: ThumbnailFormat,
+    /// The preferred width of the thumbnails.
+    pub thumbnail_width: u16,
+    /// The preferred height of the thumbnails.
+    pub thumbnail_height: u16,
+    /// Thumbnail flags:
+    /// - 0x01 - Preserve aspect ratio of the image.
+    pub thumbnail_flags: u8,
+    /// The type of the library containing the elements.
+    pub library_type: LibraryType,
+    /// The library containing the elements. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// Number of following element numbers. Written as a `u8` prior to MSEX 1.2. `0` requests all
+    /// elements.
+    pub element_count: u16,
+    /// The numbers of the elements whose thumbnails are requested.
+    pub element_numbers: Cow<'a, [u8]>,
+}
+
+/// ## MSEX / EThn - Element Thumbnail message
+///
+/// Sent by the server in response to a GETh message, carrying the thumbnail of a single element.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct EThn<'a> {
+    /// The type of the library containing the element.
+    pub library_type: LibraryType,
+    /// The library containing the element. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// The number of the element within the library.
+    pub element_number: u8,
+    /// The format of the thumbnail.
+    pub thumbnail_format: ThumbnailFormat,
+    /// The width of the thumbnail.
+    pub thumbnail_width: u16,
+    /// The height of the thumbnail.
+    pub thumbnail_height: u16,
+    /// The size of the following thumbnail buffer in bytes.
+    pub thumbnail_buffer_size: u16,
+    /// The thumbnail image data.
+    pub thumbnail_buffer: Cow<'a, [u8]>,
+}
+
+/// ## MSEX / GELT - Get Element Library Thumbnail message
+///
+/// Sent by the client to request thumbnails of libraries. The server responds with an ELTh message
+/// for each library.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct GELT<'a> {
+    /// The requested format of the thumbnails.
+    pub thumbnail_format: ThumbnailFormat,
+    /// The preferred width of the thumbnails.
+    pub thumbnail_width: u16,
+    /// The preferred height of the thumbnails.
+    pub thumbnail_height: u16,
+    /// Thumbnail flags:
+    /// - 0x01 - Preserve aspect ratio of the image.
+    pub thumbnail_flags: u8,
+    /// The type of the libraries.
+    pub library_type: LibraryType,
+    /// Number of following library identifiers. Written as a `u8` prior to MSEX 1.2. `0` requests
+    /// all libraries.
+    pub library_count: u16,
+    /// The libraries whose thumbnails are requested. Only the library numbers are carried prior to
+    /// MSEX 1.1.
+    pub library_ids: Cow<'a, [LibraryId]>,
+}
+
+/// ## MSEX / ELTh - Element Library Thumbnail message
+///
+/// Sent by the server in response to a GELT message, carrying the thumbnail of a single library.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ELTh<'a> {
+    /// The type of the library.
+    pub library_type: LibraryType,
+    /// The library. Only the library number is carried prior to MSEX 1.1.
+    pub library_id: LibraryId,
+    /// The format of the thumbnail.
+    pub thumbnail_format: ThumbnailFormat,
+    /// The width of the thumbnail.
+    pub thumbnail_width: u16,
+    /// The height of the thumbnail.
+    pub thumbnail_height: u16,
+    /// The size of the following thumbnail buffer in bytes.
+    pub thumbnail_buffer_size: u16,
+    /// The thumbnail image data.
+    pub thumbnail_buffer: Cow<'a, [u8]>,
+}
+
+/// The image formats in which video stream frames may be requested.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum StreamFormat {
+    /// A raw array of 8-bit RGB triples. The byte order is BGR in MSEX 1.0, see `bgr_to_rgb`.
+    Rgb8,
+    /// A JPEG file.
+    Jpeg,
+    /// A PNG file. Requires MSEX 1.2.
+    Png,
+    /// A JPEG file split across multiple StFr messages. Requires MSEX 1.2.
+    FragmentedJpeg,
+    /// A PNG file split across multiple StFr messages. Requires MSEX 1.2.
+    FragmentedPng,
+}
+
+/// ## MSEX / GVSr - Get Video Sources message
+///
+/// Sent by the client to request a list of the video sources available for streaming. The server
+/// responds with a VSrc message.
+#[allow(clippy::upper_case_acronyms)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GVSr;
+
+/// ## MSEX / VSrc - Video Sources message
+///
+/// Sent by the server in response to a GVSr message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VSrc<'a> {
+    /// Number of following source information blocks.
+    pub source_count: u16,
+    /// Information about each video source.
+    pub sources: Cow<'a, [SourceInformation]>,
+}
+
+/// Information about a single video source within a VSrc message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceInformation {
+    /// The identifier of the source, used when requesting a stream.
+    pub source_identifier: u16,
+    /// The display name of the source, e.g. "Output 1" or "Layer 2".
+    pub source_name: Ucs2,
+    /// The physical output of the source, or `0xFF` if it is not an output.
+    pub physical_output: u8,
+    /// The layer number of the source, or `0xFF` if it is not a layer.
+    pub layer_number: u8,
+    /// Source flags:
+    /// - 0x0001 - Without effects.
+    pub flags: u16,
+    /// The full width of the source.
+    pub width: u16,
+    /// The full height of the source.
+    pub height: u16,
+}
+
+/// ## MSEX / RqSt - Request Stream message
+///
+/// Sent by the client to request a video stream from the server. The server multicasts the stream
+/// as StFr messages. The request must be repeated before the timeout elapses in order for the
+/// stream to continue.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RqSt {
+    /// The identifier of the requested source, as listed by a VSrc message.
+    pub source_identifier: u16,
+    /// The requested format of the frames.
+    pub frame_format: StreamFormat,
+    /// The preferred width of the frames.
+    pub frame_width: u16,
+    /// The preferred height of the frames.
+    pub frame_height: u16,
+    /// The preferred number of frames per second.
+    pub fps: u8,
+    /// The number of seconds for which the stream should be sent, unless the request is renewed.
+    pub timeout: u8,
+}
+
+/// ## MSEX / StFr - Stream Frame message
+///
+/// Sent by the server to the multicast address for each frame of a requested video stream.
+///
+/// For the `FragmentedJpeg` and `FragmentedPng` formats, the frame buffer begins with a
+/// `FragmentHeader` followed by the fragment data. See `StFr::fragment`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StFr<'a> {
+    /// The UUID of the media server sending the frame. Requires MSEX 1.2.
+    pub media_server_uuid: Uuid,
+    /// The identifier of the source from which the frame originates.
+    pub source_identifier: u16,
+    /// The format of the frame.
+    pub frame_format: StreamFormat,
+    /// The width of the frame.
+    pub frame_width: u16,
+    /// The height of the frame.
+    pub frame_height: u16,
+    /// The size of the following frame buffer in bytes.
+    pub frame_buffer_size: u16,
+    /// The frame image data.
+    pub frame_buffer: Cow<'a, [u8]>,
+}
+
+/// The header that begins the frame buffer of StFr messages using a fragmented format.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct FragmentHeader {
+    /// The index of the frame to which the fragment belongs.
+    pub frame_index: u32,
+    /// The total number of fragments making up the frame.
+    pub fragment_count: u16,
+    /// The index of this fragment within the frame.
+    pub fragment_index: u16,
+    /// The offset of the fragment data within the complete frame in bytes.
+    pub fragment_byte_offset: u32,
+}
+
+/// Establishes the MSEX version used for all messages within a session.
+///
+/// For MSEX 1.2 and later the client sends a CInf listing its supported versions and the server
+/// selects the Highest Common MSEX Version, which is at least 1.2. Prior to MSEX 1.2 the server
+/// sends an unsolicited SInf carrying its single version, which the client must support.
+///
+/// Until a version has been negotiated, outgoing messages use the highest locally supported
+/// version.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VersionNegotiator {
+    supported: Vec<Version>,
+    version: Option<Version>,
+}
+
+impl Version {
+    pub const V1_0: Self = Version { major: 1, minor: 0 };
+    pub const V1_1: Self = Version { major: 1, minor: 1 };
+    pub const V1_2: Self = Version { major: 1, minor: 2 };
+
+    /// Whether or not messages of this version may be read and written by this implementation.
+    ///
+    /// Only MSEX 1.0, 1.1 and 1.2 are supported. Later versions may extend the layout of existing
+    /// messages, which could not then be read without knowledge of the additional fields.
+    pub fn is_supported(self) -> bool {
+        self.major == 1 && self.minor <= 2
+    }
+
+    /// Returns an `UnsupportedVersion` error if the version is not supported.
+    pub fn check(self) -> Result<()> {
+        if !self.is_supported() {
+            return Err(Error::UnsupportedVersion {
+                major: self.major,
+                minor: self.minor,
+            });
+        }
+        Ok(())
+    }
+
+    /// The version as carried by the CInf and SInf supported version lists.
+    pub fn to_bytes(self) -> [u8; 2] {
+        [self.major, self.minor]
+    }
+}
+
+impl From<[u8; 2]> for Version {
+    fn from([major, minor]: [u8; 2]) -> Self {
+        Version { major, minor }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+impl Uuid {
+    /// The UUID as a string, or `None` if it contains non-UTF-8 bytes.
+    pub fn as_str(&self) -> Option<&str> {
+        std::str::from_utf8(&self.0).ok()
+    }
+}
+
+impl Default for Uuid {
+    fn default() -> Self {
+        Uuid([0; 36])
+    }
+}
+
+impl From<[u8; 36]> for Uuid {
+    fn from(bytes: [u8; 36]) -> Self {
+        Uuid(bytes)
+    }
+}
+
+impl Header {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"MSEX";
+
+    /// The MSEX version of the message.
+    pub fn version(&self) -> Version {
+        Version {
+            major: self.version_major,
+            minor: self.version_minor,
+        }
+    }
+}
+
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytesVersioned,
+{
+    /// Construct a MSEX message of the given MSEX version, filling in the CITP and MSEX
+    /// headers.
+    pub fn new(version: Version, message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let msex_header = Header {
+            citp_header,
+            version_major: version.major,
+            version_minor: version.minor,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            msex_header,
+            message,
+        };
+        msg.msex_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
+impl<'a> CInf<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"CInf";
+
+    /// A CInf message listing the given supported versions with no future message data.
+    pub fn new(versions: &[Version]) -> CInf<'static> {
+        let supported_msex_versions: Vec<_> = versions.iter().map(|v| v.to_bytes()).collect();
+        CInf {
+            supported_msex_versions_count: supported_msex_versions.len() as u8,
+            supported_msex_versions: Cow::Owned(supported_msex_versions),
+            future_message_data: Cow::Owned(vec![]),
+        }
+    }
+
+    /// The MSEX versions supported by the client.
+    pub fn supported_versions(&self) -> impl Iterator<Item = Version> + '_ {
+        self.supported_msex_versions
+            .iter()
+            .map(|&v| Version::from(v))
+    }
+}
+
+impl<'a> SInf<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"SInf";
+
+    /// The MSEX versions supported by the server. Empty prior to MSEX 1.2.
+    pub fn supported_versions(&self) -> impl Iterator<Item = Version> + '_ {
+        self.supported_msex_versions
+            .iter()
+            .map(|&v| Version::from(v))
+    }
+
+    /// Whether or not the server advertises support for the given library type.
+    pub fn supports_library_type(&self, library_type: LibraryType) -> bool {
+        self.supported_library_types & library_type.bit() != 0
+    }
+}
+
+impl Nack {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"Nack";
+
+    /// A Nack refusing the message with the given header.
+    pub fn refusing(header: &Header) -> Self {
+        Nack {
+            received_content_type: header.content_type,
+        }
+    }
+}
+
+impl<'a> LSta<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"LSta";
+}
+
+impl LayerStatus {
+    pub const MEDIA_PLAYING: u32 = 0x0001;
+    pub const MEDIA_PLAYBACK_REVERSE: u32 = 0x0002;
+    pub const MEDIA_PLAYBACK_LOOPING: u32 = 0x0004;
+    pub const MEDIA_PLAYBACK_BOUNCING: u32 = 0x0008;
+    pub const MEDIA_PLAYBACK_RANDOM: u32 = 0x0010;
+    pub const MEDIA_PAUSED: u32 = 0x0020;
+}
+
+impl LibraryId {
+    /// The root library.
+    pub const ROOT: Self = LibraryId {
+        level: 0,
+        level_1: 0,
+        level_2: 0,
+        level_3: 0,
+    };
+
+    /// The level `1` identifier of an MSEX 1.0 library number.
+    pub fn from_number(number: u8) -> Self {
+        LibraryId {
+            level: 1,
+            level_1: number,
+            level_2: 0,
+            level_3: 0,
+        }
+    }
+
+    /// The library number as carried by MSEX 1.0 messages.
+    pub fn number(&self) -> u8 {
+        self.level_1
+    }
+
+    /// The number of the library within its parent library. `0` for the root.
+    pub fn number_within_parent(&self) -> u8 {
+        match self.level {
+            0 => 0,
+            1 => self.level_1,
+            2 => self.level_2,
+            _ => self.level_3,
+        }
+    }
+
+    /// The identifier of the library containing this library, or `None` for the root.
+    pub fn parent(&self) -> Option<LibraryId> {
+        let parent = match self.level {
+            0 => return None,
+            1 => LibraryId::ROOT,
+            2 => LibraryId {
+                level: 1,
+                level_2: 0,
+                ..*self
+            },
+            _ => LibraryId {
+                level: 2,
+                level_3: 0,
+                ..*self
+            },
+        };
+        Some(parent)
+    }
+}
+
+impl LibraryType {
+    /// The bit representing this library type within `SInf::supported_library_types`.
+    ///
+    /// `0` for types that cannot be represented within the 16 bits of the field.
+    pub fn bit(self) -> u16 {
+        match u8::from(self) {
+            n @ 1..=16 => 1 << (n - 1),
+            _ => 0,
+        }
+    }
+}
+
+impl From<u8> for LibraryType {
+    fn from(orig: u8) -> Self {
+        match orig {
+            1 => LibraryType::Media,
+            2 => LibraryType::Effects,
+            3 => LibraryType::Cues,
+            4 => LibraryType::Crossfades,
+            5 => LibraryType::Masks,
+            6 => LibraryType::BlendPresets,
+            7 => LibraryType::EffectPresets,
+            8 => LibraryType::ImagePresets,
+            9 => LibraryType::Meshes3D,
+            other => LibraryType::Other(other),
+        }
+    }
+}
+
+impl From<LibraryType> for u8 {
+    fn from(original: LibraryType) -> u8 {
+        match original {
+            LibraryType::Media => 1,
+            LibraryType::Effects => 2,
+            LibraryType::Cues => 3,
+            LibraryType::Crossfades => 4,
+            LibraryType::Masks => 5,
+            LibraryType::BlendPresets => 6,
+            LibraryType::EffectPresets => 7,
+            LibraryType::ImagePresets => 8,
+            LibraryType::Meshes3D => 9,
+            LibraryType::Other(other) => other,
+        }
+    }
+}
+
+impl<'a> GELI<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GELI";
+}
+
+impl<'a> ELIn<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"ELIn";
+}
+
+impl ELUp {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"ELUp";
+
+    pub const EXISTING_ELEMENTS_UPDATED: u8 = 0x01;
+    pub const ELEMENTS_ADDED_OR_REMOVED: u8 = 0x02;
+    pub const SUB_LIBRARIES_UPDATED: u8 = 0x04;
+    pub const SUB_LIBRARIES_ADDED_OR_REMOVED: u8 = 0x08;
+    pub const ALL_ELEMENTS_AFFECTED: u8 = 0x10;
+    pub const ALL_LIBRARIES_AFFECTED: u8 = 0x20;
+}
+
+impl<'a> GEIn<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GEIn";
+}
+
+impl<'a> MEIn<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"MEIn";
+}
+
+impl<'a> EEIn<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"EEIn";
+}
+
+impl<'a> GLEI<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GLEI";
+}
+
+impl ThumbnailFormat {
+    pub const RGB8: &'static [u8; 4] = b"RGB8";
+    pub const JPEG: &'static [u8; 4] = b"JPEG";
+    pub const PNG: &'static [u8; 4] = b"PNG ";
+
+    /// The cookie identifying the format.
+    pub fn cookie(&self) -> &'static [u8; 4] {
+        match *self {
+            ThumbnailFormat::Rgb8 => Self::RGB8,
+            ThumbnailFormat::Jpeg => Self::JPEG,
+            ThumbnailFormat::Png => Self::PNG,
+        }
+    }
+}
+
+impl TryFrom<u32> for ThumbnailFormat {
+    type Error = Error;
+    fn try_from(orig: u32) -> Result<Self> {
+        match &orig.to_le_bytes() {
+            Self::RGB8 => Ok(ThumbnailFormat::Rgb8),
+            Self::JPEG => Ok(ThumbnailFormat::Jpeg),
+            Self::PNG => Ok(ThumbnailFormat::Png),
+            _ => Err(Error::invalid_discriminant("ThumbnailFormat", orig)),
+        }
+    }
+}
+
+impl From<ThumbnailFormat> for u32 {
+    fn from(original: ThumbnailFormat) -> u32 {
+        u32::from_le_bytes(*original.cookie())
+    }
+}
+
+impl<'a> GETh<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GETh";
+    pub const PRESERVE_ASPECT_RATIO: u8 = 0x01;
+}
+
+impl<'a> EThn<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"EThn";
+}
+
+impl<'a> GELT<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GELT";
+    pub const PRESERVE_ASPECT_RATIO: u8 = 0x01;
+}
+
+impl<'a> ELTh<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"ELTh";
+}
+
+impl StreamFormat {
+    pub const RGB8: &'static [u8; 4] = b"RGB8";
+    pub const JPEG: &'static [u8; 4] = b"JPEG";
+    pub const PNG: &'static [u8; 4] = b"PNG ";
+    pub const FRAGMENTED_JPEG: &'static [u8; 4] = b"fJPG";
+    pub const FRAGMENTED_PNG: &'static [u8; 4] = b"fPNG";
+
+    /// The cookie identifying the format.
+    pub fn cookie(&self) -> &'static [u8; 4] {
+        match *self {
+            StreamFormat::Rgb8 => Self::RGB8,
+            StreamFormat::Jpeg => Self::JPEG,
+            StreamFormat::Png => Self::PNG,
+            StreamFormat::FragmentedJpeg => Self::FRAGMENTED_JPEG,
+            StreamFormat::FragmentedPng => Self::FRAGMENTED_PNG,
+        }
+    }
+
+    /// Whether or not frames of this format are split across multiple StFr messages.
+    pub fn is_fragmented(&self) -> bool {
+        matches!(
+            *self,
+            StreamFormat::FragmentedJpeg | StreamFormat::FragmentedPng
+        )
+    }
+}
+
+impl TryFrom<u32> for StreamFormat {
+    type Error = Error;
+    fn try_from(orig: u32) -> Result<Self> {
+        match &orig.to_le_bytes() {
+            Self::RGB8 => Ok(StreamFormat::Rgb8),
+            Self::JPEG => Ok(StreamFormat::Jpeg),
+            Self::PNG => Ok(StreamFormat::Png),
+            Self::FRAGMENTED_JPEG => Ok(StreamFormat::FragmentedJpeg),
+            Self::FRAGMENTED_PNG => Ok(StreamFormat::FragmentedPng),
+            _ => Err(Error::invalid_discriminant("StreamFormat", orig)),
+        }
+    }
+}
+
+impl From<StreamFormat> for u32 {
+    fn from(original: StreamFormat) -> u32 {
+        u32::from_le_bytes(*original.cookie())
+    }
+}
+
+impl GVSr {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"GVSr";
+}
+
+impl<'a> VSrc<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"VSrc";
+}
+
+impl SourceInformation {
+    pub const WITHOUT_EFFECTS: u16 = 0x0001;
+    /// The value of `physical_output` or `layer_number` when the field does not apply.
+    pub const NONE: u8 = 0xFF;
+}
+
+impl RqSt {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"RqSt";
+}
+
+impl<'a> StFr<'a> {
+    pub const CONTENT_TYPE: &'static [u8; 4] = b"StFr";
+
+    /// Split the frame buffer of a fragmented frame into its `FragmentHeader` and fragment data.
+    ///
+    /// Returns `Ok(None)` if the frame format is not fragmented, or an error if the frame buffer
+    /// is too short to contain a fragment header.
+    pub fn fragment(&self) -> Result<Option<(FragmentHeader, &[u8])>> {
+        if !self.frame_format.is_fragmented() {
+            return Ok(None);
+        }
+        if self.frame_buffer.len() < FragmentHeader::SIZE_BYTES {
+            return Err(Error::Truncated {
+                expected: FragmentHeader::SIZE_BYTES,
+                actual: self.frame_buffer.len(),
+            });
+        }
+        let (header, data) = self.frame_buffer.split_at(FragmentHeader::SIZE_BYTES);
+        let header = (&header[..]).read_bytes()?;
+        Ok(Some((header, data)))
+    }
+}
+
+impl VersionNegotiator {
+    /// Negotiate between the given locally supported versions.
+    ///
+    /// **Panics** if `supported` is empty.
+    pub fn new<I>(supported: I) -> Self
+    where
+        I: IntoIterator<Item = Version>,
+    {
+        let mut supported: Vec<_> = supported.into_iter().collect();
+        assert!(
+            !supported.is_empty(),
+            "at least one version must be supported"
+        );
+        supported.sort();
+        supported.dedup();
+        VersionNegotiator {
+            supported,
+            version: None,
+        }
+    }
+
+    /// The locally supported versions in ascending order.
+    pub fn supported(&self) -> &[Version] {
+        &self.supported
+    }
+
+    /// The negotiated session version, if any.
+    pub fn version(&self) -> Option<Version> {
+        self.version
+    }
+
+    /// The version used for outgoing messages.
+    ///
+    /// This is the negotiated version if there is one, otherwise the highest supported version.
+    pub fn outgoing_version(&self) -> Version {
+        self.version.unwrap_or_else(|| {
+            *self
+                .supported
+                .last()
+                .expect("at least one version is supported")
+        })
+    }
+
+    /// The CInf message that a client sends immediately after connecting to a server.
+    pub fn cinf(&self) -> CInf<'static> {
+        CInf::new(&self.supported)
+    }
+
+    /// Server side: select the Highest Common MSEX Version from a client's CInf message.
+    ///
+    /// Returns a `NoCommonVersion` error if the client and server share no version of 1.2 or
+    /// later.
+    pub fn receive_cinf(&mut self, cinf: &CInf) -> Result<Version> {
+        let version = cinf
+            .supported_versions()
+            .filter(|v| *v >= Version::V1_2 && self.supported.contains(v))
+            .max()
+            .ok_or(Error::NoCommonVersion)?;
+        self.version = Some(version);
+        Ok(version)
+    }
+
+    /// Server side: adopt the highest supported version prior to MSEX 1.2 for a client that did
+    /// not send a CInf message.
+    ///
+    /// This is the version of the unsolicited SInf message the server then sends. Returns `None`
+    /// without changing the session version if no version prior to 1.2 is supported.
+    pub fn select_legacy_version(&mut self) -> Option<Version> {
+        let version = self
+            .supported
+            .iter()
+            .filter(|&&v| v < Version::V1_2)
+            .max()
+            .cloned()?;
+        self.version = Some(version);
+        Some(version)
+    }
+
+    /// Client side: adopt the version of a server's SInf message.
+    ///
+    /// The version of the SInf message header is the session version. Returns a
+    /// `NoCommonVersion` error if that version is not supported locally.
+    pub fn receive_sinf(&mut self, sinf: &Message<SInf>) -> Result<Version> {
+        let version = sinf.msex_header.version();
+        version.check()?;
+        if !self.supported.contains(&version) {
+            return Err(Error::NoCommonVersion);
+        }
+        self.version = Some(version);
+        Ok(version)
+    }
+
+    /// Construct a message using the outgoing version.
+    pub fn message<T>(&self, message: T) -> Message<T>
+    where
+        T: ContentType + SizeBytesVersioned,
+    {
+        Message::new(self.outgoing_version(), message)
+    }
+
+    /// Check that a received message uses the negotiated version.
+    ///
+    /// CInf and SInf messages are exempt as they are used to establish the version. Messages
+    /// received prior to negotiation are checked against the supported versions. Returns a
+    /// `VersionMismatch` error for messages that should be refused with a Nack.
+    pub fn check(&self, header: &Header) -> Result<()> {
+        let exempt = [CInf::COOKIE.get(), SInf::COOKIE.get()];
+        if exempt.contains(&header.content_type) {
+            return Ok(());
+        }
+        let version = header.version();
+        let accepted = match self.version {
+            Some(v) => v == version,
+            None => self.supported.contains(&version),
+        };
+        if !accepted {
+            let expected = self.outgoing_version();
+            return Err(Error::VersionMismatch {
+                expected_major: expected.major,
+                expected_minor: expected.minor,
+                major: version.major,
+                minor: version.minor,
+            });
+        }
+        Ok(())
+    }
+
+    /// Forget the negotiated version, e.g. after the connection is closed.
+    pub fn reset(&mut self) {
+        self.version = None;
+    }
+}
+
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for CInf<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for SInf<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for Nack {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for LSta<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for GELI<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for ELIn<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for ELUp {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for GEIn<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for MEIn<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for EEIn<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for GLEI<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for GETh<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for EThn<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for GELT<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for ELTh<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for GVSr {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for VSrc<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for RqSt {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for StFr<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<T> WriteToBytesVersioned for T
+where
+    T: WriteToBytes,
+{
+    fn write_to_bytes_versioned<W: WriteBytesExt>(&self, _: Version, writer: W) -> Result<()> {
+        self.write_to_bytes(writer)
+    }
+}
+
+impl<T> ReadFromBytesVersioned for T
+where
+    T: ReadFromBytes,
+{
+    fn read_from_bytes_versioned<R: ReadBytesExt>(_: Version, reader: R) -> Result<Self> {
+        T::read_from_bytes(reader)
+    }
+}
+
+impl<T> SizeBytesVersioned for T
+where
+    T: SizeBytes,
+{
+    fn size_bytes_versioned(&self, _: Version) -> usize {
+        self.size_bytes()
+    }
+}
+
+impl WriteToBytes for Header {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_bytes(self.citp_header)?;
+        writer.write_u8(self.version_major)?;
+        writer.write_u8(self.version_minor)?;
+        writer.write_u32::<LE>(self.content_type)?;
+        Ok(())
+    }
+}
+
+impl<T> WriteToBytes for Message<T>
+where
+    T: WriteToBytesVersioned,
+{
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_bytes(self.msex_header)?;
+        self.message
+            .write_to_bytes_versioned(self.msex_header.version(), writer)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytes for CInf<'a> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count(
+            "supported_msex_versions_count",
+            self.supported_msex_versions_count as _,
+            self.supported_msex_versions.len(),
+        )?;
+        writer.write_u8(self.supported_msex_versions_count)?;
+        for &[major, minor] in self.supported_msex_versions.iter() {
+            writer.write_u16::<LE>(u16::from_be_bytes([major, minor]))?;
+        }
+        writer.write_all(&self.future_message_data)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for SInf<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "layer_count",
+            self.layer_count as _,
+            self.layer_dmx_sources.len(),
+        )?;
+        if version < Version::V1_2 {
+            writer.write_bytes(&self.product_name)?;
+            writer.write_u8(self.product_version_major)?;
+            writer.write_u8(self.product_version_minor)?;
+        } else {
+            protocol::check_count(
+                "supported_msex_versions_count",
+                self.supported_msex_versions_count as _,
+                self.supported_msex_versions.len(),
+            )?;
+            protocol::check_count(
+                "thumbnail_formats_count",
+                self.thumbnail_formats_count as _,
+                self.thumbnail_formats.len(),
+            )?;
+            protocol::check_count(
+                "stream_formats_count",
+                self.stream_formats_count as _,
+                self.stream_formats.len(),
+            )?;
+            writer.write_all(&self.uuid.0)?;
+            writer.write_bytes(&self.product_name)?;
+            writer.write_u8(self.product_version_major)?;
+            writer.write_u8(self.product_version_minor)?;
+            writer.write_u8(self.product_version_bugfix)?;
+            writer.write_u8(self.supported_msex_versions_count)?;
+            for &[major, minor] in self.supported_msex_versions.iter() {
+                writer.write_u16::<LE>(u16::from_be_bytes([major, minor]))?;
+            }
+            writer.write_u16::<LE>(self.supported_library_types)?;
+            writer.write_u8(self.thumbnail_formats_count)?;
+            for &format in self.thumbnail_formats.iter() {
+                writer.write_u32::<LE>(format)?;
+            }
+            writer.write_u8(self.stream_formats_count)?;
+            for &format in self.stream_formats.iter() {
+                writer.write_u32::<LE>(format)?;
+            }
+        }
+        writer.write_u8(self.layer_count)?;
+        for source in self.layer_dmx_sources.iter() {
+            writer.write_bytes(source)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytes for Nack {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u32::<LE>(self.received_content_type)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for LibraryId {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u8(self.level)?;
+        writer.write_u8(self.level_1)?;
+        writer.write_u8(self.level_2)?;
+        writer.write_u8(self.level_3)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for LSta<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count("layer_count", self.layer_count as _, self.layers.len())?;
+        writer.write_u8(self.layer_count)?;
+        for layer in self.layers.iter() {
+            layer.write_to_bytes_versioned(version, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytesVersioned for LayerStatus {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        writer.write_u8(self.layer_number)?;
+        writer.write_u8(self.physical_output)?;
+        if version < Version::V1_2 {
+            writer.write_u8(self.media_library_id.number())?;
+        } else {
+            writer.write_bytes(self.media_library_type)?;
+            writer.write_bytes(self.media_library_id)?;
+        }
+        writer.write_u8(self.media_number)?;
+        writer.write_bytes(&self.media_name)?;
+        writer.write_u32::<LE>(self.media_position)?;
+        writer.write_u32::<LE>(self.media_length)?;
+        writer.write_u8(self.media_fps)?;
+        writer.write_u32::<LE>(self.layer_status_flags)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for LibraryType {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u8((*self).into())?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for GELI<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "library_count",
+            self.library_count as _,
+            self.library_numbers.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        if version >= Version::V1_1 {
+            writer.write_bytes(self.library_parent_id)?;
+        }
+        write_count(version, "library_count", self.library_count, &mut writer)?;
+        writer.write_all(&self.library_numbers)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for ELIn<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "library_count",
+            self.library_count as _,
+            self.libraries.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        write_count(version, "library_count", self.library_count, &mut writer)?;
+        for library in self.libraries.iter() {
+            library.write_to_bytes_versioned(version, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytesVersioned for LibraryInformation {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        write_library_id(version, self.id, &mut writer)?;
+        if version >= Version::V1_2 {
+            writer.write_u32::<LE>(self.serial_number)?;
+        }
+        writer.write_u8(self.dmx_range_min)?;
+        writer.write_u8(self.dmx_range_max)?;
+        writer.write_bytes(&self.name)?;
+        if version >= Version::V1_1 {
+            write_count(version, "library_count", self.library_count, &mut writer)?;
+        }
+        write_count(version, "element_count", self.element_count, &mut writer)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytesVersioned for ELUp {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        writer.write_bytes(self.library_type)?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        writer.write_u8(self.update_flags)?;
+        if version >= Version::V1_2 {
+            writer.write_all(&self.affected_elements)?;
+            writer.write_all(&self.affected_libraries)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for GEIn<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "element_count",
+            self.element_count as _,
+            self.element_numbers.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        write_count(version, "element_count", self.element_count, &mut writer)?;
+        writer.write_all(&self.element_numbers)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for MEIn<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "element_count",
+            self.element_count as _,
+            self.elements.len(),
+        )?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        write_count(version, "element_count", self.element_count, &mut writer)?;
+        for element in self.elements.iter() {
+            element.write_to_bytes_versioned(version, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytesVersioned for MediaInformation {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        writer.write_u8(self.number)?;
+        if version >= Version::V1_2 {
+            writer.write_u32::<LE>(self.serial_number)?;
+        }
+        writer.write_u8(self.dmx_range_min)?;
+        writer.write_u8(self.dmx_range_max)?;
+        writer.write_bytes(&self.media_name)?;
+        writer.write_u64::<LE>(self.media_version_timestamp)?;
+        writer.write_u16::<LE>(self.media_width)?;
+        writer.write_u16::<LE>(self.media_height)?;
+        writer.write_u32::<LE>(self.media_length)?;
+        writer.write_u8(self.media_fps)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for EEIn<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count("effect_count", self.effect_count as _, self.effects.len())?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        write_count(version, "effect_count", self.effect_count, &mut writer)?;
+        for effect in self.effects.iter() {
+            effect.write_to_bytes_versioned(version, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for EffectInformation<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        protocol::check_count(
+            "effect_parameter_count",
+            self.effect_parameter_count as _,
+            self.effect_parameter_names.len(),
+        )?;
+        writer.write_u8(self.element_number)?;
+        if version >= Version::V1_2 {
+            writer.write_u32::<LE>(self.serial_number)?;
+        }
+        writer.write_u8(self.dmx_range_min)?;
+        writer.write_u8(self.dmx_range_max)?;
+        writer.write_bytes(&self.effect_name)?;
+        writer.write_u8(self.effect_parameter_count)?;
+        for name in self.effect_parameter_names.iter() {
+            writer.write_bytes(name)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for GLEI<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        check_glei_version(version)?;
+        protocol::check_count(
+            "element_count",
+            self.element_count as _,
+            self.elements.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        writer.write_bytes(self.library_id)?;
+        write_count(version, "element_count", self.element_count, &mut writer)?;
+        for element in self.elements.iter() {
+            element.write_to_bytes_versioned(version, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytesVersioned for GenericInformation {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        writer.write_u8(self.element_number)?;
+        if version >= Version::V1_2 {
+            writer.write_u32::<LE>(self.serial_number)?;
+        }
+        writer.write_u8(self.dmx_range_min)?;
+        writer.write_u8(self.dmx_range_max)?;
+        writer.write_bytes(&self.name)?;
+        writer.write_u64::<LE>(self.version_timestamp)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for ThumbnailFormat {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u32::<LE>((*self).into())?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for GETh<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "element_count",
+            self.element_count as _,
+            self.element_numbers.len(),
+        )?;
+        writer.write_bytes(self.thumbnail_format)?;
+        writer.write_u16::<LE>(self.thumbnail_width)?;
+        writer.write_u16::<LE>(self.thumbnail_height)?;
+        writer.write_u8(self.thumbnail_flags)?;
+        writer.write_bytes(self.library_type)?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        write_count(version, "element_count", self.element_count, &mut writer)?;
+        writer.write_all(&self.element_numbers)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for EThn<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "thumbnail_buffer_size",
+            self.thumbnail_buffer_size as _,
+            self.thumbnail_buffer.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        writer.write_u8(self.element_number)?;
+        writer.write_bytes(self.thumbnail_format)?;
+        writer.write_u16::<LE>(self.thumbnail_width)?;
+        writer.write_u16::<LE>(self.thumbnail_height)?;
+        writer.write_u16::<LE>(self.thumbnail_buffer_size)?;
+        writer.write_all(&self.thumbnail_buffer)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for GELT<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "library_count",
+            self.library_count as _,
+            self.library_ids.len(),
+        )?;
+        writer.write_bytes(self.thumbnail_format)?;
+        writer.write_u16::<LE>(self.thumbnail_width)?;
+        writer.write_u16::<LE>(self.thumbnail_height)?;
+        writer.write_u8(self.thumbnail_flags)?;
+        writer.write_bytes(self.library_type)?;
+        write_count(version, "library_count", self.library_count, &mut writer)?;
+        for &id in self.library_ids.iter() {
+            write_library_id(version, id, &mut writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for ELTh<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "thumbnail_buffer_size",
+            self.thumbnail_buffer_size as _,
+            self.thumbnail_buffer.len(),
+        )?;
+        writer.write_bytes(self.library_type)?;
+        write_library_id(version, self.library_id, &mut writer)?;
+        writer.write_bytes(self.thumbnail_format)?;
+        writer.write_u16::<LE>(self.thumbnail_width)?;
+        writer.write_u16::<LE>(self.thumbnail_height)?;
+        writer.write_u16::<LE>(self.thumbnail_buffer_size)?;
+        writer.write_all(&self.thumbnail_buffer)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for StreamFormat {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u32::<LE>((*self).into())?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for GVSr {
+    fn write_to_bytes<W: WriteBytesExt>(&self, _writer: W) -> Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytes for VSrc<'a> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        protocol::check_count("source_count", self.source_count as _, self.sources.len())?;
+        writer.write_u16::<LE>(self.source_count)?;
+        for source in self.sources.iter() {
+            writer.write_bytes(source)?;
+        }
+        Ok(())
+    }
+}
+
+impl WriteToBytes for SourceInformation {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u16::<LE>(self.source_identifier)?;
+        writer.write_bytes(&self.source_name)?;
+        writer.write_u8(self.physical_output)?;
+        writer.write_u8(self.layer_number)?;
+        writer.write_u16::<LE>(self.flags)?;
+        writer.write_u16::<LE>(self.width)?;
+        writer.write_u16::<LE>(self.height)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for RqSt {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u16::<LE>(self.source_identifier)?;
+        writer.write_bytes(self.frame_format)?;
+        writer.write_u16::<LE>(self.frame_width)?;
+        writer.write_u16::<LE>(self.frame_height)?;
+        writer.write_u8(self.fps)?;
+        writer.write_u8(self.timeout)?;
+        Ok(())
+    }
+}
+
+impl<'a> WriteToBytesVersioned for StFr<'a> {
+    fn write_to_bytes_versioned<W: WriteBytesExt>(
+        &self,
+        version: Version,
+        mut writer: W,
+    ) -> Result<()> {
+        version.check()?;
+        protocol::check_count(
+            "frame_buffer_size",
+            self.frame_buffer_size as _,
+            self.frame_buffer.len(),
+        )?;
+        if version >= Version::V1_2 {
+            writer.write_all(&self.media_server_uuid.0)?;
+        }
+        writer.write_u16::<LE>(self.source_identifier)?;
+        writer.write_bytes(self.frame_format)?;
+        writer.write_u16::<LE>(self.frame_width)?;
+        writer.write_u16::<LE>(self.frame_height)?;
+        writer.write_u16::<LE>(self.frame_buffer_size)?;
+        writer.write_all(&self.frame_buffer)?;
+        Ok(())
+    }
+}
+
+impl WriteToBytes for FragmentHeader {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_u32::<LE>(self.frame_index)?;
+        writer.write_u16::<LE>(self.fragment_count)?;
+        writer.write_u16::<LE>(self.fragment_index)?;
+        writer.write_u32::<LE>(self.fragment_byte_offset)?;
+        Ok(())
+    }
+}
+
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let version_major = reader.read_u8()?;
+        let version_minor = reader.read_u8()?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            version_major,
+            version_minor,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytesVersioned,
+{
+    /// Read the message using the MSEX version of its header, validating the layer and message
+    /// cookies and the `message_size` of the base header.
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let msex_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, msex_header.content_type)?;
+        let message = T::read_from_bytes_versioned(msex_header.version(), &mut reader)?;
+        protocol::check_message_size(&msex_header.citp_header, reader.count())?;
+        let msg = Message {
+            msex_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
+impl ReadFromBytes for CInf<'static> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let supported_msex_versions_count = reader.read_u8()?;
+        let mut supported_msex_versions = Vec::with_capacity(supported_msex_versions_count as _);
+        for _ in 0..supported_msex_versions_count {
+            let version: u16 = reader.read_bytes()?;
+            supported_msex_versions.push(version.to_be_bytes());
+        }
+        let mut future_message_data = vec![];
+        reader.read_to_end(&mut future_message_data)?;
+        let cinf = CInf {
+            supported_msex_versions_count,
+            supported_msex_versions: Cow::Owned(supported_msex_versions),
+            future_message_data: Cow::Owned(future_message_data),
+        };
+        Ok(cinf)
+    }
+}
+
+impl ReadFromBytesVersioned for SInf<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let mut sinf = SInf {
+            uuid: Uuid::default(),
+            product_name: Ucs2::default(),
+            product_version_major: 0,
+            product_version_minor: 0,
+            product_version_bugfix: 0,
+            supported_msex_versions_count: 0,
+            supported_msex_versions: Cow::Owned(vec![]),
+            supported_library_types: 0,
+            thumbnail_formats_count: 0,
+            thumbnail_formats: Cow::Owned(vec![]),
+            stream_formats_count: 0,
+            stream_formats: Cow::Owned(vec![]),
+            layer_count: 0,
+            layer_dmx_sources: Cow::Owned(vec![]),
+        };
+        if version < Version::V1_2 {
+            sinf.product_name = reader.read_bytes()?;
+            sinf.product_version_major = reader.read_u8()?;
+            sinf.product_version_minor = reader.read_u8()?;
+        } else {
+            reader.read_exact(&mut sinf.uuid.0)?;
+            sinf.product_name = reader.read_bytes()?;
+            sinf.product_version_major = reader.read_u8()?;
+            sinf.product_version_minor = reader.read_u8()?;
+            sinf.product_version_bugfix = reader.read_u8()?;
+            sinf.supported_msex_versions_count = reader.read_u8()?;
+            let mut versions = Vec::with_capacity(sinf.supported_msex_versions_count as _);
+            for _ in 0..sinf.supported_msex_versions_count {
+                let version: u16 = reader.read_bytes()?;
+                versions.push(version.to_be_bytes());
+            }
+            sinf.supported_msex_versions = Cow::Owned(versions);
+            sinf.supported_library_types = reader.read_u16::<LE>()?;
+            sinf.thumbnail_formats_count = reader.read_u8()?;
+            let mut formats = Vec::with_capacity(sinf.thumbnail_formats_count as _);
+            for _ in 0..sinf.thumbnail_formats_count {
+                formats.push(reader.read_u32::<LE>()?);
+            }
+            sinf.thumbnail_formats = Cow::Owned(formats);
+            sinf.stream_formats_count = reader.read_u8()?;
+            let mut formats = Vec::with_capacity(sinf.stream_formats_count as _);
+            for _ in 0..sinf.stream_formats_count {
+                formats.push(reader.read_u32::<LE>()?);
+            }
+            sinf.stream_formats = Cow::Owned(formats);
+        }
+        sinf.layer_count = reader.read_u8()?;
+        let mut sources = Vec::with_capacity(sinf.layer_count as _);
+        for _ in 0..sinf.layer_count {
+            sources.push(reader.read_bytes()?);
+        }
+        sinf.layer_dmx_sources = Cow::Owned(sources);
+        Ok(sinf)
+    }
+}
+
+impl ReadFromBytes for Nack {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let received_content_type = reader.read_u32::<LE>()?;
+        let nack = Nack {
+            received_content_type,
+        };
+        Ok(nack)
+    }
+}
+
+impl ReadFromBytes for LibraryId {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let level = reader.read_u8()?;
+        let level_1 = reader.read_u8()?;
+        let level_2 = reader.read_u8()?;
+        let level_3 = reader.read_u8()?;
+        let id = LibraryId {
+            level,
+            level_1,
+            level_2,
+            level_3,
+        };
+        Ok(id)
+    }
+}
+
+impl ReadFromBytesVersioned for LSta<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let layer_count = reader.read_u8()?;
+        let mut layers = Vec::with_capacity(layer_count as _);
+        for _ in 0..layer_count {
+            layers.push(LayerStatus::read_from_bytes_versioned(
+                version,
+                &mut reader,
+            )?);
+        }
+        let lsta = LSta {
+            layer_count,
+            layers: Cow::Owned(layers),
+        };
+        Ok(lsta)
+    }
+}
+
+impl ReadFromBytesVersioned for LayerStatus {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        let layer_number = reader.read_u8()?;
+        let physical_output = reader.read_u8()?;
+        let (media_library_type, media_library_id) = if version < Version::V1_2 {
+            (
+                LibraryType::Media,
+                LibraryId::from_number(reader.read_u8()?),
+            )
+        } else {
+            (reader.read_bytes()?, reader.read_bytes()?)
+        };
+        let media_number = reader.read_u8()?;
+        let media_name = reader.read_bytes()?;
+        let media_position = reader.read_u32::<LE>()?;
+        let media_length = reader.read_u32::<LE>()?;
+        let media_fps = reader.read_u8()?;
+        let layer_status_flags = reader.read_u32::<LE>()?;
+        let status = LayerStatus {
+            layer_number,
+            physical_output,
+            media_library_type,
+            media_library_id,
+            media_number,
+            media_name,
+            media_position,
+            media_length,
+            media_fps,
+            layer_status_flags,
+        };
+        Ok(status)
+    }
+}
+
+impl ReadFromBytes for LibraryType {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        Ok(reader.read_u8()?.into())
+    }
+}
+
+impl ReadFromBytesVersioned for GELI<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_parent_id = if version >= Version::V1_1 {
+            reader.read_bytes()?
+        } else {
+            LibraryId::ROOT
+        };
+        let library_count = read_count(version, &mut reader)?;
+        let library_numbers = protocol::read_new_bytes(&mut reader, library_count as _)?;
+        let geli = GELI {
+            library_type,
+            library_parent_id,
+            library_count,
+            library_numbers: Cow::Owned(library_numbers),
+        };
+        Ok(geli)
+    }
+}
+
+impl ReadFromBytesVersioned for ELIn<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_count = read_count(version, &mut reader)?;
+        let mut libraries = Vec::with_capacity(library_count as _);
+        for _ in 0..library_count {
+            libraries.push(LibraryInformation::read_from_bytes_versioned(
+                version,
+                &mut reader,
+            )?);
+        }
+        let elin = ELIn {
+            library_type,
+            library_count,
+            libraries: Cow::Owned(libraries),
+        };
+        Ok(elin)
+    }
+}
+
+impl ReadFromBytesVersioned for LibraryInformation {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        let id = read_library_id(version, &mut reader)?;
+        let serial_number = if version >= Version::V1_2 {
+            reader.read_u32::<LE>()?
+        } else {
+            0
+        };
+        let dmx_range_min = reader.read_u8()?;
+        let dmx_range_max = reader.read_u8()?;
+        let name = reader.read_bytes()?;
+        let library_count = if version >= Version::V1_1 {
+            read_count(version, &mut reader)?
+        } else {
+            0
+        };
+        let element_count = read_count(version, &mut reader)?;
+        let info = LibraryInformation {
+            id,
+            serial_number,
+            dmx_range_min,
+            dmx_range_max,
+            name,
+            library_count,
+            element_count,
+        };
+        Ok(info)
+    }
+}
+
+impl ReadFromBytesVersioned for ELUp {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let update_flags = reader.read_u8()?;
+        let mut affected_elements = [0; 32];
+        let mut affected_libraries = [0; 32];
+        if version >= Version::V1_2 {
+            reader.read_exact(&mut affected_elements)?;
+            reader.read_exact(&mut affected_libraries)?;
+        }
+        let elup = ELUp {
+            library_type,
+            library_id,
+            update_flags,
+            affected_elements,
+            affected_libraries,
+        };
+        Ok(elup)
+    }
+}
+
+impl ReadFromBytesVersioned for GEIn<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let element_count = read_count(version, &mut reader)?;
+        let element_numbers = protocol::read_new_bytes(&mut reader, element_count as _)?;
+        let gein = GEIn {
+            library_type,
+            library_id,
+            element_count,
+            element_numbers: Cow::Owned(element_numbers),
+        };
+        Ok(gein)
+    }
+}
+
+impl ReadFromBytesVersioned for MEIn<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let element_count = read_count(version, &mut reader)?;
+        let mut elements = Vec::with_capacity(element_count as _);
+        for _ in 0..element_count {
+            elements.push(MediaInformation::read_from_bytes_versioned(
+                version,
+                &mut reader,
+            )?);
+        }
+        let mein = MEIn {
+            library_id,
+            element_count,
+            elements: Cow::Owned(elements),
+        };
+        Ok(mein)
+    }
+}
+
+impl ReadFromBytesVersioned for MediaInformation {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        let number = reader.read_u8()?;
+        let serial_number = if version >= Version::V1_2 {
+            reader.read_u32::<LE>()?
+        } else {
+            0
+        };
+        let dmx_range_min = reader.read_u8()?;
+        let dmx_range_max = reader.read_u8()?;
+        let media_name = reader.read_bytes()?;
+        let media_version_timestamp = reader.read_u64::<LE>()?;
+        let media_width = reader.read_u16::<LE>()?;
+        let media_height = reader.read_u16::<LE>()?;
+        let media_length = reader.read_u32::<LE>()?;
+        let media_fps = reader.read_u8()?;
+        let info = MediaInformation {
+            number,
+            serial_number,
+            dmx_range_min,
+            dmx_range_max,
+            media_name,
+            media_version_timestamp,
+            media_width,
+            media_height,
+            media_length,
+            media_fps,
+        };
+        Ok(info)
+    }
+}
+
+impl ReadFromBytesVersioned for EEIn<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let effect_count = read_count(version, &mut reader)?;
+        let mut effects = Vec::with_capacity(effect_count as _);
+        for _ in 0..effect_count {
+            effects.push(EffectInformation::read_from_bytes_versioned(
+                version,
+                &mut reader,
+            )?);
+        }
+        let eein = EEIn {
+            library_id,
+            effect_count,
+            effects: Cow::Owned(effects),
+        };
+        Ok(eein)
+    }
+}
+
+impl ReadFromBytesVersioned for EffectInformation<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        let element_number = reader.read_u8()?;
+        let serial_number = if version >= Version::V1_2 {
+            reader.read_u32::<LE>()?
+        } else {
+            0
+        };
+        let dmx_range_min = reader.read_u8()?;
+        let dmx_range_max = reader.read_u8()?;
+        let effect_name = reader.read_bytes()?;
+        let effect_parameter_count = reader.read_u8()?;
+        let mut effect_parameter_names = Vec::with_capacity(effect_parameter_count as _);
+        for _ in 0..effect_parameter_count {
+            effect_parameter_names.push(reader.read_bytes()?);
+        }
+        let info = EffectInformation {
+            element_number,
+            serial_number,
+            dmx_range_min,
+            dmx_range_max,
+            effect_name,
+            effect_parameter_count,
+            effect_parameter_names: Cow::Owned(effect_parameter_names),
+        };
+        Ok(info)
+    }
+}
+
+impl ReadFromBytesVersioned for GLEI<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        check_glei_version(version)?;
+        let library_type = reader.read_bytes()?;
+        let library_id = reader.read_bytes()?;
+        let element_count = read_count(version, &mut reader)?;
+        let mut elements = Vec::with_capacity(element_count as _);
+        for _ in 0..element_count {
+            elements.push(GenericInformation::read_from_bytes_versioned(
+                version,
+                &mut reader,
+            )?);
+        }
+        let glei = GLEI {
+            library_type,
+            library_id,
+            element_count,
+            elements: Cow::Owned(elements),
+        };
+        Ok(glei)
+    }
+}
+
+impl ReadFromBytesVersioned for GenericInformation {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        let element_number = reader.read_u8()?;
+        let serial_number = if version >= Version::V1_2 {
+            reader.read_u32::<LE>()?
+        } else {
+            0
+        };
+        let dmx_range_min = reader.read_u8()?;
+        let dmx_range_max = reader.read_u8()?;
+        let name = reader.read_bytes()?;
+        let version_timestamp = reader.read_u64::<LE>()?;
+        let info = GenericInformation {
+            element_number,
+            serial_number,
+            dmx_range_min,
+            dmx_range_max,
+            name,
+            version_timestamp,
+        };
+        Ok(info)
+    }
+}
+
+impl ReadFromBytes for ThumbnailFormat {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        reader.read_u32::<LE>()?.try_into()
+    }
+}
+
+impl ReadFromBytesVersioned for GETh<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let thumbnail_format = reader.read_bytes()?;
+        let thumbnail_width = reader.read_u16::<LE>()?;
+        let thumbnail_height = reader.read_u16::<LE>()?;
+        let thumbnail_flags = reader.read_u8()?;
+        let library_type = reader.read_bytes()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let element_count = read_count(version, &mut reader)?;
+        let element_numbers = protocol::read_new_bytes(&mut reader, element_count as _)?;
+        let geth = GETh {
+            thumbnail_format,
+            thumbnail_width,
+            thumbnail_height,
+            thumbnail_flags,
+            library_type,
+            library_id,
+            element_count,
+            element_numbers: Cow::Owned(element_numbers),
+        };
+        Ok(geth)
+    }
+}
+
+impl ReadFromBytesVersioned for EThn<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let element_number = reader.read_u8()?;
+        let thumbnail_format = reader.read_bytes()?;
+        let thumbnail_width = reader.read_u16::<LE>()?;
+        let thumbnail_height = reader.read_u16::<LE>()?;
+        let thumbnail_buffer_size = reader.read_u16::<LE>()?;
+        let thumbnail_buffer = protocol::read_new_bytes(&mut reader, thumbnail_buffer_size as _)?;
+        let ethn = EThn {
+            library_type,
+            library_id,
+            element_number,
+            thumbnail_format,
+            thumbnail_width,
+            thumbnail_height,
+            thumbnail_buffer_size,
+            thumbnail_buffer: Cow::Owned(thumbnail_buffer),
+        };
+        Ok(ethn)
+    }
+}
+
+impl ReadFromBytesVersioned for GELT<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let thumbnail_format = reader.read_bytes()?;
+        let thumbnail_width = reader.read_u16::<LE>()?;
+        let thumbnail_height = reader.read_u16::<LE>()?;
+        let thumbnail_flags = reader.read_u8()?;
+        let library_type = reader.read_bytes()?;
+        let library_count = read_count(version, &mut reader)?;
+        let mut library_ids = Vec::with_capacity(library_count as _);
+        for _ in 0..library_count {
+            library_ids.push(read_library_id(version, &mut reader)?);
+        }
+        let gelt = GELT {
+            thumbnail_format,
+            thumbnail_width,
+            thumbnail_height,
+            thumbnail_flags,
+            library_type,
+            library_count,
+            library_ids: Cow::Owned(library_ids),
+        };
+        Ok(gelt)
+    }
+}
+
+impl ReadFromBytesVersioned for ELTh<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let library_type = reader.read_bytes()?;
+        let library_id = read_library_id(version, &mut reader)?;
+        let thumbnail_format = reader.read_bytes()?;
+        let thumbnail_width = reader.read_u16::<LE>()?;
+        let thumbnail_height = reader.read_u16::<LE>()?;
+        let thumbnail_buffer_size = reader.read_u16::<LE>()?;
+        let thumbnail_buffer = protocol::read_new_bytes(&mut reader, thumbnail_buffer_size as _)?;
+        let elth = ELTh {
+            library_type,
+            library_id,
+            thumbnail_format,
+            thumbnail_width,
+            thumbnail_height,
+            thumbnail_buffer_size,
+            thumbnail_buffer: Cow::Owned(thumbnail_buffer),
+        };
+        Ok(elth)
+    }
+}
+
+impl ReadFromBytes for StreamFormat {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        reader.read_u32::<LE>()?.try_into()
+    }
+}
+
+impl ReadFromBytes for GVSr {
+    fn read_from_bytes<R: ReadBytesExt>(_reader: R) -> Result<Self> {
+        Ok(GVSr)
+    }
+}
+
+impl ReadFromBytes for VSrc<'static> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let source_count = reader.read_u16::<LE>()?;
+        let mut sources = Vec::with_capacity(source_count as _);
+        for _ in 0..source_count {
+            sources.push(reader.read_bytes()?);
+        }
+        let vsrc = VSrc {
+            source_count,
+            sources: Cow::Owned(sources),
+        };
+        Ok(vsrc)
+    }
+}
+
+impl ReadFromBytes for SourceInformation {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let source_identifier = reader.read_u16::<LE>()?;
+        let source_name = reader.read_bytes()?;
+        let physical_output = reader.read_u8()?;
+        let layer_number = reader.read_u8()?;
+        let flags = reader.read_u16::<LE>()?;
+        let width = reader.read_u16::<LE>()?;
+        let height = reader.read_u16::<LE>()?;
+        let source = SourceInformation {
+            source_identifier,
+            source_name,
+            physical_output,
+            layer_number,
+            flags,
+            width,
+            height,
+        };
+        Ok(source)
+    }
+}
+
+impl ReadFromBytes for RqSt {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let source_identifier = reader.read_u16::<LE>()?;
+        let frame_format = reader.read_bytes()?;
+        let frame_width = reader.read_u16::<LE>()?;
+        let frame_height = reader.read_u16::<LE>()?;
+        let fps = reader.read_u8()?;
+        let timeout = reader.read_u8()?;
+        let rqst = RqSt {
+            source_identifier,
+            frame_format,
+            frame_width,
+            frame_height,
+            fps,
+            timeout,
+        };
+        Ok(rqst)
+    }
+}
+
+impl ReadFromBytesVersioned for StFr<'static> {
+    fn read_from_bytes_versioned<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<Self> {
+        version.check()?;
+        let mut media_server_uuid = Uuid::default();
+        if version >= Version::V1_2 {
+            reader.read_exact(&mut media_server_uuid.0)?;
+        }
+        let source_identifier = reader.read_u16::<LE>()?;
+        let frame_format = reader.read_bytes()?;
+        let frame_width = reader.read_u16::<LE>()?;
+        let frame_height = reader.read_u16::<LE>()?;
+        let frame_buffer_size = reader.read_u16::<LE>()?;
+        let frame_buffer = protocol::read_new_bytes(&mut reader, frame_buffer_size as _)?;
+        let stfr = StFr {
+            media_server_uuid,
+            source_identifier,
+            frame_format,
+            frame_width,
+            frame_height,
+            frame_buffer_size,
+            frame_buffer: Cow::Owned(frame_buffer),
+        };
+        Ok(stfr)
+    }
+}
+
+impl ReadFromBytes for FragmentHeader {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let frame_index = reader.read_u32::<LE>()?;
+        let fragment_count = reader.read_u16::<LE>()?;
+        let fragment_index = reader.read_u16::<LE>()?;
+        let fragment_byte_offset = reader.read_u32::<LE>()?;
+        let header = FragmentHeader {
+            frame_index,
+            fragment_count,
+            fragment_index,
+            fragment_byte_offset,
+        };
+        Ok(header)
+    }
+}
+
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytesVersioned,
+{
+    fn size_bytes(&self) -> usize {
+        self.msex_header.size_bytes()
+            + self
+                .message
+                .size_bytes_versioned(self.msex_header.version())
+    }
+}
+
+impl<'a> SizeBytes for CInf<'a> {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u8>()
+            + self.supported_msex_versions.len() * mem::size_of::<u16>()
+            + self.future_message_data.len()
+    }
+}
+
+impl<'a> SizeBytesVersioned for SInf<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let common = self.product_name.size_bytes()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self
+                .layer_dmx_sources
+                .iter()
+                .map(|source| source.size_bytes())
+                .sum::<usize>();
+        if version < Version::V1_2 {
+            return common;
+        }
+        common
+            + self.uuid.0.len()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self.supported_msex_versions.len() * mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u8>()
+            + self.thumbnail_formats.len() * mem::size_of::<u32>()
+            + mem::size_of::<u8>()
+            + self.stream_formats.len() * mem::size_of::<u32>()
+    }
+}
+
+impl SizeBytes for Nack {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u32>()
+    }
+}
+
+impl SizeBytes for LibraryId {
+    fn size_bytes(&self) -> usize {
+        Self::SIZE_BYTES
+    }
+}
+
+impl ConstSizeBytes for LibraryId {
+    const SIZE_BYTES: usize = 4;
+}
+
+impl<'a> SizeBytesVersioned for LSta<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        mem::size_of::<u8>()
+            + self
+                .layers
+                .iter()
+                .map(|layer| layer.size_bytes_versioned(version))
+                .sum::<usize>()
+    }
+}
+
+impl SizeBytesVersioned for LayerStatus {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let library = if version < Version::V1_2 {
+            mem::size_of::<u8>()
+        } else {
+            mem::size_of::<u8>() + LibraryId::SIZE_BYTES
+        };
+        mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + library
+            + mem::size_of::<u8>()
+            + self.media_name.size_bytes()
+            + mem::size_of::<u32>()
+            + mem::size_of::<u32>()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u32>()
+    }
+}
+
+impl SizeBytes for LibraryType {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u8>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for GELI<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let parent_id = if version >= Version::V1_1 {
+            LibraryId::SIZE_BYTES
+        } else {
+            0
+        };
+        self.library_type.size_bytes()
+            + parent_id
+            + count_size(version)
+            + self.library_numbers.len()
+    }
+}
+
+impl<'a> SizeBytesVersioned for ELIn<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.library_type.size_bytes()
+            + count_size(version)
+            + self
+                .libraries
+                .iter()
+                .map(|library| library.size_bytes_versioned(version))
+                .sum::<usize>()
+    }
+}
+
+impl SizeBytesVersioned for LibraryInformation {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let serial_number = if version >= Version::V1_2 {
+            mem::size_of::<u32>()
+        } else {
+            0
+        };
+        let library_count = if version >= Version::V1_1 {
+            count_size(version)
+        } else {
+            0
+        };
+        library_id_size(version)
+            + serial_number
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self.name.size_bytes()
+            + library_count
+            + count_size(version)
+    }
+}
+
+impl SizeBytesVersioned for ELUp {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let affected = if version >= Version::V1_2 {
+            self.affected_elements.len() + self.affected_libraries.len()
+        } else {
+            0
+        };
+        self.library_type.size_bytes() + library_id_size(version) + mem::size_of::<u8>() + affected
+    }
+}
+
+impl<'a> SizeBytesVersioned for GEIn<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.library_type.size_bytes()
+            + library_id_size(version)
+            + count_size(version)
+            + self.element_numbers.len()
+    }
+}
+
+impl<'a> SizeBytesVersioned for MEIn<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        library_id_size(version)
+            + count_size(version)
+            + self
+                .elements
+                .iter()
+                .map(|element| element.size_bytes_versioned(version))
+                .sum::<usize>()
+    }
+}
+
+impl SizeBytesVersioned for MediaInformation {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        mem::size_of::<u8>()
+            + serial_number_size(version)
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self.media_name.size_bytes()
+            + mem::size_of::<u64>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u32>()
+            + mem::size_of::<u8>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for EEIn<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        library_id_size(version)
+            + count_size(version)
+            + self
+                .effects
+                .iter()
+                .map(|effect| effect.size_bytes_versioned(version))
+                .sum::<usize>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for EffectInformation<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        mem::size_of::<u8>()
+            + serial_number_size(version)
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self.effect_name.size_bytes()
+            + mem::size_of::<u8>()
+            + self
+                .effect_parameter_names
+                .iter()
+                .map(|name| name.size_bytes())
+                .sum::<usize>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for GLEI<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.library_type.size_bytes()
+            + LibraryId::SIZE_BYTES
+            + count_size(version)
+            + self
+                .elements
+                .iter()
+                .map(|element| element.size_bytes_versioned(version))
+                .sum::<usize>()
+    }
+}
+
+impl SizeBytesVersioned for GenericInformation {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        mem::size_of::<u8>()
+            + serial_number_size(version)
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + self.name.size_bytes()
+            + mem::size_of::<u64>()
+    }
+}
+
+impl SizeBytes for ThumbnailFormat {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u32>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for GETh<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.thumbnail_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u8>()
+            + self.library_type.size_bytes()
+            + library_id_size(version)
+            + count_size(version)
+            + self.element_numbers.len()
+    }
+}
+
+impl<'a> SizeBytesVersioned for EThn<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.library_type.size_bytes()
+            + library_id_size(version)
+            + mem::size_of::<u8>()
+            + self.thumbnail_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + self.thumbnail_buffer.len()
+    }
+}
+
+impl<'a> SizeBytesVersioned for GELT<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.thumbnail_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u8>()
+            + self.library_type.size_bytes()
+            + count_size(version)
+            + self.library_ids.len() * library_id_size(version)
+    }
+}
+
+impl<'a> SizeBytesVersioned for ELTh<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        self.library_type.size_bytes()
+            + library_id_size(version)
+            + self.thumbnail_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + self.thumbnail_buffer.len()
+    }
+}
+
+impl SizeBytes for StreamFormat {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u32>()
+    }
+}
+
+impl SizeBytes for GVSr {
+    fn size_bytes(&self) -> usize {
+        0
+    }
+}
+
+impl<'a> SizeBytes for VSrc<'a> {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u16>()
+            + self
+                .sources
+                .iter()
+                .map(|source| source.size_bytes())
+                .sum::<usize>()
+    }
+}
+
+impl SizeBytes for SourceInformation {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u16>()
+            + self.source_name.size_bytes()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+    }
+}
+
+impl SizeBytes for RqSt {
+    fn size_bytes(&self) -> usize {
+        mem::size_of::<u16>()
+            + self.frame_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u8>()
+            + mem::size_of::<u8>()
+    }
+}
+
+impl<'a> SizeBytesVersioned for StFr<'a> {
+    fn size_bytes_versioned(&self, version: Version) -> usize {
+        let uuid = if version >= Version::V1_2 {
+            self.media_server_uuid.0.len()
+        } else {
+            0
+        };
+        uuid + mem::size_of::<u16>()
+            + self.frame_format.size_bytes()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + mem::size_of::<u16>()
+            + self.frame_buffer.len()
+    }
+}
+
+impl SizeBytes for FragmentHeader {
+    fn size_bytes(&self) -> usize {
+        Self::SIZE_BYTES
+    }
+}
+
+impl ConstSizeBytes for FragmentHeader {
+    const SIZE_BYTES: usize = 12;
+}
+
+/// Convert RGB8 image data in MSEX 1.0 BGR byte order to RGB byte order, in place.
+///
+/// MSEX 1.0 RGB8 images are an array of BGR triples, while later versions use RGB triples. As the
+/// conversion swaps the first and third byte of each triple, it may also be used to convert RGB
+/// data to BGR prior to sending it to an MSEX 1.0 peer. Any trailing bytes that do not form a
+/// whole triple are left unchanged.
+pub fn bgr_to_rgb(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(3) {
+        pixel.swap(0, 2);
+    }
+}
+
+/// GLEI was introduced in MSEX 1.1.
+fn check_glei_version(version: Version) -> Result<()> {
+    version.check()?;
+    if version < Version::V1_1 {
+        return Err(Error::UnsupportedVersion {
+            major: version.major,
+            minor: version.minor,
+        });
+    }
+    Ok(())
+}
+
+/// Element serial numbers were introduced in MSEX 1.2.
+fn serial_number_size(version: Version) -> usize {
+    if version < Version::V1_2 {
+        0
+    } else {
+        mem::size_of::<u32>()
+    }
+}
+
+/// Library identifiers are a single library number prior to MSEX 1.1.
+fn write_library_id<W: WriteBytesExt>(
+    version: Version,
+    id: LibraryId,
+    mut writer: W,
+) -> Result<()> {
+    if version < Version::V1_1 {
+        writer.write_u8(id.number())?;
+    } else {
+        writer.write_bytes(id)?;
+    }
+    Ok(())
+}
+
+fn read_library_id<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<LibraryId> {
+    if version < Version::V1_1 {
+        Ok(LibraryId::from_number(reader.read_u8()?))
+    } else {
+        reader.read_bytes()
+    }
+}
+
+fn library_id_size(version: Version) -> usize {
+    if version < Version::V1_1 {
+        mem::size_of::<u8>()
+    } else {
+        LibraryId::SIZE_BYTES
+    }
+}
+
+/// Library and element counts are widened from a `u8` to a `u16` in MSEX 1.2.
+fn write_count<W: WriteBytesExt>(
+    version: Version,
+    field: &'static str,
+    count: u16,
+    mut writer: W,
+) -> Result<()> {
+    if version < Version::V1_2 {
+        let count = u8::try_from(count).map_err(|_| Error::CountOverflow {
+            field,
+            count: count as usize,
+            max: u8::MAX as usize,
+        })?;
+        writer.write_u8(count)?;
+    } else {
+        writer.write_u16::<LE>(count)?;
+    }
+    Ok(())
+}
+
+fn read_count<R: ReadBytesExt>(version: Version, mut reader: R) -> Result<u16> {
+    if version < Version::V1_2 {
+        Ok(reader.read_u8()? as u16)
+    } else {
+        Ok(reader.read_u16::<LE>()?)
+    }
+}
+
+fn count_size(version: Version) -> usize {
+    if version < Version::V1_2 {
+        mem::size_of::<u8>()
+    } else {
+        mem::size_of::<u16>()
+    }
+}
@@ -0,0 +1,427 @@
+use super::{
+    bgr_to_rgb, ELUp, EffectInformation, GenericInformation, LayerStatus, LibraryId,
+    LibraryInformation, LibraryType, MediaInformation, SInf, ThumbnailFormat, Version,
+};
+use crate::protocol::{MsexMessage, Ucs2};
+use std::collections::{BTreeMap, HashMap};
+
+/// A mirror of the state of a single media server, maintained from the MSEX messages it sends.
+///
+/// The state is updated by passing each decoded MSEX message received from the server to
+/// `update`, which returns the resulting changes. No network IO is performed, allowing the state
+/// to be driven from any connection or from recorded packets.
+///
+/// The state is made up of:
+///
+/// - the server information from the most recent SInf message,
+/// - the status of each layer from the most recent LSta message,
+/// - the library tree from ELIn messages, invalidated by ELUp messages. A GELI request may ask for
+///   only some of the children of a library, so an ELIn message only adds or updates the libraries
+///   it lists and libraries are only removed by ELUp messages,
+/// - element information from MEIn, EEIn and GLEI messages, invalidated by ELUp messages and
+/// - thumbnails from EThn and ELTh messages.
+///
+/// Note that the server only describes its libraries and elements in response to requests sent by
+/// the client, e.g. GELI or GEIn. Requests are typically sent in response to the returned events.
+#[derive(Clone, Debug, Default)]
+pub struct MediaServerState {
+    version: Option<Version>,
+    server_info: Option<SInf<'static>>,
+    layers: BTreeMap<u8, LayerStatus>,
+    libraries: BTreeMap<(LibraryType, LibraryId), LibraryInformation>,
+    elements: BTreeMap<(LibraryType, LibraryId, u8), Element>,
+    library_thumbnails: HashMap<(LibraryType, LibraryId), Thumbnail>,
+    element_thumbnails: HashMap<(LibraryType, LibraryId, u8), Thumbnail>,
+}
+
+/// Information about a single element, as described by MEIn, EEIn or GLEI messages.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    /// An element of a media library, described by a MEIn message.
+    Media(MediaInformation),
+    /// An element of an effects library, described by an EEIn message.
+    Effect(EffectInformation<'static>),
+    /// An element of any other library type, described by a GLEI message.
+    Generic(GenericInformation),
+}
+
+/// A thumbnail received via an EThn or ELTh message.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Thumbnail {
+    /// The format of the thumbnail data.
+    pub format: ThumbnailFormat,
+    /// The width of the thumbnail.
+    pub width: u16,
+    /// The height of the thumbnail.
+    pub height: u16,
+    /// The image data. `Rgb8` data is always in RGB byte order, even when received via MSEX 1.0.
+    pub data: Vec<u8>,
+}
+
+/// Changes to the `MediaServerState`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MediaServerEvent {
+    /// A SInf message describing new server information was received.
+    ServerInfoChanged,
+    /// The media on the given layer started playing.
+    LayerStartedPlaying { layer_number: u8 },
+    /// The media on the given layer stopped playing.
+    LayerStoppedPlaying { layer_number: u8 },
+    /// A different media was selected on the given layer.
+    LayerMediaChanged { layer_number: u8 },
+    /// An ELIn message describing libraries of the given type was received.
+    LibrariesReceived { library_type: LibraryType },
+    /// The server announced that the given library has changed via an ELUp message.
+    ///
+    /// Affected libraries and elements have been removed from the state and should be requested
+    /// again if required.
+    LibraryUpdated {
+        library_type: LibraryType,
+        library_id: LibraryId,
+        update_flags: u8,
+    },
+    /// A MEIn, EEIn or GLEI message describing elements of the given library was received.
+    ElementsReceived {
+        library_type: LibraryType,
+        library_id: LibraryId,
+    },
+    /// A thumbnail of the given library was received.
+    LibraryThumbnailReceived {
+        library_type: LibraryType,
+        library_id: LibraryId,
+    },
+    /// A thumbnail of the given element was received.
+    ElementThumbnailReceived {
+        library_type: LibraryType,
+        library_id: LibraryId,
+        element_number: u8,
+    },
+}
+
+impl MediaServerState {
+    /// Create an empty state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the state with a message received from the media server.
+    ///
+    /// Returns the changes that resulted from the message. Messages that do not describe the
+    /// state of the server, e.g. requests or StFr messages, are ignored.
+    pub fn update(&mut self, message: &MsexMessage) -> Vec<MediaServerEvent> {
+        let mut events = vec![];
+        match *message {
+            MsexMessage::SInf(ref msg) => {
+                self.version = Some(msg.msex_header.version());
+                if self.server_info.as_ref() != Some(&msg.message) {
+                    self.server_info = Some(msg.message.clone());
+                    events.push(MediaServerEvent::ServerInfoChanged);
+                }
+            }
+            MsexMessage::LSta(ref msg) => {
+                let mut layers = BTreeMap::new();
+                for layer in msg.message.layers.iter() {
+                    let prev = self.layers.get(&layer.layer_number);
+                    events.extend(layer_events(prev, layer));
+                    layers.insert(layer.layer_number, layer.clone());
+                }
+                self.layers = layers;
+            }
+            MsexMessage::ELIn(ref msg) => {
+                let library_type = msg.message.library_type;
+                for library in msg.message.libraries.iter() {
+                    self.libraries
+                        .insert((library_type, library.id), library.clone());
+                }
+                events.push(MediaServerEvent::LibrariesReceived { library_type });
+            }
+            MsexMessage::ELUp(ref msg) => {
+                let version = msg.msex_header.version();
+                self.invalidate(version, &msg.message);
+                events.push(MediaServerEvent::LibraryUpdated {
+                    library_type: msg.message.library_type,
+                    library_id: msg.message.library_id,
+                    update_flags: msg.message.update_flags,
+                });
+            }
+            MsexMessage::MEIn(ref msg) => {
+                let library_type = LibraryType::Media;
+                let library_id = msg.message.library_id;
+                for media in msg.message.elements.iter() {
+                    let key = (library_type, library_id, media.number);
+                    self.elements.insert(key, Element::Media(media.clone()));
+                }
+                events.push(MediaServerEvent::ElementsReceived {
+                    library_type,
+                    library_id,
+                });
+            }
+            MsexMessage::EEIn(ref msg) => {
+                let library_type = LibraryType::Effects;
+                let library_id = msg.message.library_id;
+                for effect in msg.message.effects.iter() {
+                    let key = (library_type, library_id, effect.element_number);
+                    self.elements.insert(key, Element::Effect(effect.clone()));
+                }
+                events.push(MediaServerEvent::ElementsReceived {
+                    library_type,
+                    library_id,
+                });
+            }
+            MsexMessage::GLEI(ref msg) => {
+                let library_type = msg.message.library_type;
+                let library_id = msg.message.library_id;
+                for element in msg.message.elements.iter() {
+                    let key = (library_type, library_id, element.element_number);
+                    self.elements.insert(key, Element::Generic(element.clone()));
+                }
+                events.push(MediaServerEvent::ElementsReceived {
+                    library_type,
+                    library_id,
+                });
+            }
+            MsexMessage::EThn(ref msg) => {
+                let ethn = &msg.message;
+                let thumbnail = Thumbnail::new(
+                    msg.msex_header.version(),
+                    ethn.thumbnail_format,
+                    ethn.thumbnail_width,
+                    ethn.thumbnail_height,
+                    &ethn.thumbnail_buffer,
+                );
+                let key = (ethn.library_type, ethn.library_id, ethn.element_number);
+                self.element_thumbnails.insert(key, thumbnail);
+                events.push(MediaServerEvent::ElementThumbnailReceived {
+                    library_type: ethn.library_type,
+                    library_id: ethn.library_id,
+                    element_number: ethn.element_number,
+                });
+            }
+            MsexMessage::ELTh(ref msg) => {
+                let elth = &msg.message;
+                let thumbnail = Thumbnail::new(
+                    msg.msex_header.version(),
+                    elth.thumbnail_format,
+                    elth.thumbnail_width,
+                    elth.thumbnail_height,
+                    &elth.thumbnail_buffer,
+                );
+                let key = (elth.library_type, elth.library_id);
+                self.library_thumbnails.insert(key, thumbnail);
+                events.push(MediaServerEvent::LibraryThumbnailReceived {
+                    library_type: elth.library_type,
+                    library_id: elth.library_id,
+                });
+            }
+            _ => (),
+        }
+        events
+    }
+
+    /// The MSEX version of the most recent SInf message.
+    pub fn version(&self) -> Option<Version> {
+        self.version
+    }
+
+    /// The most recently received server information.
+    pub fn server_info(&self) -> Option<&SInf<'static>> {
+        self.server_info.as_ref()
+    }
+
+    /// The status of the given layer.
+    pub fn layer(&self, layer_number: u8) -> Option<&LayerStatus> {
+        self.layers.get(&layer_number)
+    }
+
+    /// The status of all layers, ordered by layer number.
+    pub fn layers(&self) -> impl Iterator<Item = &LayerStatus> {
+        self.layers.values()
+    }
+
+    /// Information about the given library.
+    pub fn library(
+        &self,
+        library_type: LibraryType,
+        library_id: LibraryId,
+    ) -> Option<&LibraryInformation> {
+        self.libraries.get(&(library_type, library_id))
+    }
+
+    /// All known libraries of the given type, ordered by library identifier.
+    pub fn libraries(
+        &self,
+        library_type: LibraryType,
+    ) -> impl Iterator<Item = &LibraryInformation> {
+        self.libraries
+            .iter()
+            .filter(move |&(&(ty, _), _)| ty == library_type)
+            .map(|(_, library)| library)
+    }
+
+    /// All known libraries of the given type whose parent is `parent_id`.
+    pub fn sub_libraries(
+        &self,
+        library_type: LibraryType,
+        parent_id: LibraryId,
+    ) -> impl Iterator<Item = &LibraryInformation> {
+        self.libraries(library_type)
+            .filter(move |library| library.id.parent() == Some(parent_id))
+    }
+
+    /// Information about the given element.
+    pub fn element(
+        &self,
+        library_type: LibraryType,
+        library_id: LibraryId,
+        element_number: u8,
+    ) -> Option<&Element> {
+        self.elements
+            .get(&(library_type, library_id, element_number))
+    }
+
+    /// All known elements of the given library, ordered by element number.
+    pub fn elements(
+        &self,
+        library_type: LibraryType,
+        library_id: LibraryId,
+    ) -> impl Iterator<Item = &Element> {
+        self.elements
+            .range((library_type, library_id, 0)..=(library_type, library_id, u8::MAX))
+            .map(|(_, element)| element)
+    }
+
+    /// The thumbnail of the given library.
+    pub fn library_thumbnail(
+        &self,
+        library_type: LibraryType,
+        library_id: LibraryId,
+    ) -> Option<&Thumbnail> {
+        self.library_thumbnails.get(&(library_type, library_id))
+    }
+
+    /// The thumbnail of the given element.
+    pub fn element_thumbnail(
+        &self,
+        library_type: LibraryType,
+        library_id: LibraryId,
+        element_number: u8,
+    ) -> Option<&Thumbnail> {
+        self.element_thumbnails
+            .get(&(library_type, library_id, element_number))
+    }
+
+    /// Forget all state, e.g. after reconnecting to the server.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Remove the libraries and elements affected by the given ELUp message.
+    ///
+    /// Prior to MSEX 1.2 the affected elements and sub-libraries are not specified, so all are
+    /// removed.
+    fn invalidate(&mut self, version: Version, elup: &ELUp) {
+        let library_type = elup.library_type;
+        let library_id = elup.library_id;
+        let flags = elup.update_flags;
+
+        let elements_changed = ELUp::EXISTING_ELEMENTS_UPDATED | ELUp::ELEMENTS_ADDED_OR_REMOVED;
+        if flags & elements_changed != 0 {
+            let all = version < Version::V1_2 || flags & ELUp::ALL_ELEMENTS_AFFECTED != 0;
+            let affected = |&(ty, id, number): &(LibraryType, LibraryId, u8)| {
+                ty == library_type
+                    && id == library_id
+                    && (all || is_set(&elup.affected_elements, number))
+            };
+            self.elements.retain(|key, _| !affected(key));
+            self.element_thumbnails.retain(|key, _| !affected(key));
+        }
+
+        let libraries_changed = ELUp::SUB_LIBRARIES_UPDATED | ELUp::SUB_LIBRARIES_ADDED_OR_REMOVED;
+        if flags & libraries_changed != 0 {
+            let all = version < Version::V1_2 || flags & ELUp::ALL_LIBRARIES_AFFECTED != 0;
+            let affected = |&(ty, id): &(LibraryType, LibraryId)| {
+                ty == library_type
+                    && id.parent() == Some(library_id)
+                    && (all || is_set(&elup.affected_libraries, id.number_within_parent()))
+            };
+            self.libraries.retain(|key, _| !affected(key));
+            self.library_thumbnails.retain(|key, _| !affected(key));
+        }
+    }
+}
+
+impl Element {
+    /// The number of the element within its library.
+    pub fn number(&self) -> u8 {
+        match *self {
+            Element::Media(ref media) => media.number,
+            Element::Effect(ref effect) => effect.element_number,
+            Element::Generic(ref generic) => generic.element_number,
+        }
+    }
+
+    /// The display name of the element.
+    pub fn name(&self) -> &Ucs2 {
+        match *self {
+            Element::Media(ref media) => &media.media_name,
+            Element::Effect(ref effect) => &effect.effect_name,
+            Element::Generic(ref generic) => &generic.name,
+        }
+    }
+}
+
+impl Thumbnail {
+    /// Copy the received thumbnail data, converting MSEX 1.0 `Rgb8` data to RGB byte order.
+    fn new(
+        version: Version,
+        format: ThumbnailFormat,
+        width: u16,
+        height: u16,
+        buffer: &[u8],
+    ) -> Self {
+        let mut data = buffer.to_vec();
+        if format == ThumbnailFormat::Rgb8 && version < Version::V1_1 {
+            bgr_to_rgb(&mut data);
+        }
+        Thumbnail {
+            format,
+            width,
+            height,
+            data,
+        }
+    }
+}
+
+/// The events resulting from a change in the status of a layer.
+fn layer_events(prev: Option<&LayerStatus>, layer: &LayerStatus) -> Vec<MediaServerEvent> {
+    let layer_number = layer.layer_number;
+    let playing = layer.layer_status_flags & LayerStatus::MEDIA_PLAYING != 0;
+    let mut events = vec![];
+    match prev {
+        None => {
+            if playing {
+                events.push(MediaServerEvent::LayerStartedPlaying { layer_number });
+            }
+        }
+        Some(prev) => {
+            let media_changed = prev.media_library_type != layer.media_library_type
+                || prev.media_library_id != layer.media_library_id
+                || prev.media_number != layer.media_number;
+            if media_changed {
+                events.push(MediaServerEvent::LayerMediaChanged { layer_number });
+            }
+            let was_playing = prev.layer_status_flags & LayerStatus::MEDIA_PLAYING != 0;
+            if playing && !was_playing {
+                events.push(MediaServerEvent::LayerStartedPlaying { layer_number });
+            } else if !playing && was_playing {
+                events.push(MediaServerEvent::LayerStoppedPlaying { layer_number });
+            }
+        }
+    }
+    events
+}
+
+/// Whether the bit for the given number is set within a 256 bit set of an ELUp message.
+fn is_set(bits: &[u8; 32], number: u8) -> bool {
+    bits[number as usize / 8] & (1 << (number % 8)) != 0
+}
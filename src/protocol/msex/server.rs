@@ -0,0 +1,779 @@
+use super::{
+    bgr_to_rgb, CInf, ContentType, EEIn, ELIn, ELTh, EThn, EffectInformation, GEIn, GETh,
+    GenericInformation, LSta, LayerStatus, LibraryId, LibraryInformation, LibraryType, MEIn,
+    MediaInformation, Message, Nack, RqSt, SInf, SizeBytesVersioned, SourceInformation, StFr,
+    StreamFormat, ThumbnailFormat, Uuid, VSrc, Version, VersionNegotiator, WriteToBytesVersioned,
+    GELI, GELT, GLEI,
+};
+use crate::net::tcp::Session;
+use crate::protocol::{self, pinf, Kind, MsexMessage, Packet, ReadBytes, Ucs2, WriteBytes};
+use crate::{Error, Result};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr, SocketAddrV4, TcpListener, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The largest image generated for thumbnails and stream frames, leaving room for the headers of
+/// a StFr message within a single UDP datagram.
+const MAX_IMAGE_BYTES: usize = 60_000;
+/// The dimensions of thumbnails and frames requested with a width or height of `0`.
+const DEFAULT_IMAGE_SIZE: (u16, u16) = (64, 48);
+/// How long to block waiting for a message before performing periodic work.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A media server that serves an in-memory library over a single localhost TCP session.
+///
+/// Intended for testing clients without a real media server. The server:
+///
+/// - performs the CInf/SInf handshake, falling back to sending an MSEX 1.0 or 1.1 SInf message if
+///   the client does not send a CInf message within `HANDSHAKE_TIMEOUT`,
+/// - answers GELI, GEIn, GETh and GELT requests from its libraries, generating solid-colour RGB8
+///   thumbnails and refusing requests for unknown libraries with a Nack,
+/// - sends an LSta message describing its layers at a regular interval,
+/// - answers GVSr requests with its video sources and RqSt requests by sending solid-colour RGB8
+///   StFr frames to the stream address until the request times out and
+/// - responds to any other message with a Nack, where the session version allows it. This
+///   includes MSEX messages that cannot be decoded and those of a version other than the session
+///   version.
+#[derive(Debug)]
+pub struct MockMediaServer {
+    listener: TcpListener,
+    uuid: Uuid,
+    product_name: String,
+    versions: Vec<Version>,
+    layers: Vec<LayerStatus>,
+    libraries: Vec<MockLibrary>,
+    sources: Vec<MockSource>,
+    lsta_interval: Duration,
+    stream_addr: SocketAddr,
+}
+
+/// A library served by the `MockMediaServer`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MockLibrary {
+    pub library_type: LibraryType,
+    pub id: LibraryId,
+    pub name: String,
+    /// The colour of the library's thumbnail.
+    pub colour: [u8; 3],
+    pub elements: Vec<MockElement>,
+}
+
+/// An element within a `MockLibrary`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MockElement {
+    pub number: u8,
+    pub name: String,
+    /// The colour of the element's thumbnail.
+    pub colour: [u8; 3],
+}
+
+/// A video source that may be streamed from the `MockMediaServer`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MockSource {
+    pub identifier: u16,
+    pub name: String,
+    pub width: u16,
+    pub height: u16,
+    /// The colour of each frame of the stream.
+    pub colour: [u8; 3],
+}
+
+/// The state of a single served session.
+struct Connection<'a> {
+    server: &'a MockMediaServer,
+    session: Session,
+    socket: UdpSocket,
+    negotiator: VersionNegotiator,
+    connected: Instant,
+    last_lsta: Option<Instant>,
+    streams: Vec<Stream>,
+}
+
+/// A stream requested via RqSt.
+struct Stream {
+    source_identifier: u16,
+    width: u16,
+    height: u16,
+    interval: Duration,
+    next_frame: Instant,
+    deadline: Instant,
+}
+
+impl MockMediaServer {
+    /// The UUID of the server unless otherwise specified.
+    pub const DEFAULT_UUID: &'static [u8; 36] = b"00000000-0000-4000-8000-00000000c17b";
+    /// How long to wait for a CInf message before assuming the client predates MSEX 1.2.
+    pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(1);
+    /// The default interval between LSta messages.
+    pub const DEFAULT_LSTA_INTERVAL: Duration = Duration::from_millis(250);
+
+    /// Listen for a client on an unused localhost port.
+    ///
+    /// The server supports MSEX 1.0 to 1.2 and serves a media library and an effects library,
+    /// a single layer and a single video source. See `local_addr` for the port.
+    pub fn bind() -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+        Ok(MockMediaServer {
+            listener,
+            uuid: Uuid(*Self::DEFAULT_UUID),
+            product_name: "Mock Media Server".to_string(),
+            versions: vec![Version::V1_0, Version::V1_1, Version::V1_2],
+            layers: vec![default_layer()],
+            libraries: default_libraries(),
+            sources: vec![MockSource {
+                identifier: 1,
+                name: "Output 1".to_string(),
+                width: 64,
+                height: 48,
+                colour: [0, 0, 255],
+            }],
+            lsta_interval: Self::DEFAULT_LSTA_INTERVAL,
+            stream_addr: SocketAddr::from((pinf::MULTICAST_ADDR, pinf::MULTICAST_PORT)),
+        })
+    }
+
+    /// Specify the UUID announced via SInf and StFr messages.
+    pub fn with_uuid(mut self, uuid: Uuid) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    /// Specify the supported MSEX versions.
+    ///
+    /// **Panics** if `versions` is empty.
+    pub fn with_versions(mut self, versions: &[Version]) -> Self {
+        assert!(
+            !versions.is_empty(),
+            "at least one MSEX version must be supported"
+        );
+        self.versions = versions.to_vec();
+        self
+    }
+
+    /// Specify the layers described by LSta messages.
+    pub fn with_layers(mut self, layers: Vec<LayerStatus>) -> Self {
+        self.layers = layers;
+        self
+    }
+
+    /// Specify the libraries that are served.
+    pub fn with_libraries(mut self, libraries: Vec<MockLibrary>) -> Self {
+        self.libraries = libraries;
+        self
+    }
+
+    /// Specify the video sources that may be streamed.
+    pub fn with_sources(mut self, sources: Vec<MockSource>) -> Self {
+        self.sources = sources;
+        self
+    }
+
+    /// Specify the interval between LSta messages.
+    pub fn with_lsta_interval(mut self, interval: Duration) -> Self {
+        self.lsta_interval = interval;
+        self
+    }
+
+    /// Specify the address to which StFr messages are sent. The MSEX multicast address by
+    /// default.
+    pub fn with_stream_addr(mut self, addr: SocketAddr) -> Self {
+        self.stream_addr = addr;
+        self
+    }
+
+    /// The address on which the server accepts a client.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Block until a client connects, then serve it until it disconnects.
+    pub fn serve_one(&self) -> Result<()> {
+        let (stream, _) = self.listener.accept()?;
+        let session = Session::new(stream)?;
+        session.set_read_timeout(Some(POLL_INTERVAL))?;
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+        socket.bind(&SocketAddrV4::new(Ipv4Addr::LOCALHOST, 0).into())?;
+        socket.set_multicast_if_v4(&Ipv4Addr::LOCALHOST)?;
+        socket.set_multicast_loop_v4(true)?;
+        let mut connection = Connection {
+            server: self,
+            session,
+            socket: socket.into(),
+            negotiator: VersionNegotiator::new(self.versions.iter().cloned()),
+            connected: Instant::now(),
+            last_lsta: None,
+            streams: vec![],
+        };
+        connection.run()
+    }
+
+    /// Serve a single client on a new thread.
+    pub fn spawn(self) -> thread::JoinHandle<Result<()>> {
+        thread::spawn(move || self.serve_one())
+    }
+
+    fn library(&self, library_type: LibraryType, id: LibraryId) -> Option<&MockLibrary> {
+        self.libraries
+            .iter()
+            .find(|library| library.library_type == library_type && library.id == id)
+    }
+
+    fn sinf(&self, version: Version) -> SInf<'static> {
+        let versions: Vec<[u8; 2]> = self.versions.iter().map(|v| v.to_bytes()).collect();
+        let library_types = self
+            .libraries
+            .iter()
+            .fold(0, |bits, library| bits | library.library_type.bit());
+        let formats = vec![u32::from(ThumbnailFormat::Rgb8)];
+        let layer_dmx_sources: Vec<CString> = (0..self.layers.len())
+            .map(|i| CString::new(format!("ArtNet/0/0/{}", 1 + i * 16)).unwrap())
+            .collect();
+        let mut sinf = SInf {
+            uuid: self.uuid,
+            product_name: ucs2(&self.product_name),
+            product_version_major: 1,
+            product_version_minor: 0,
+            product_version_bugfix: 0,
+            supported_msex_versions_count: versions.len() as _,
+            supported_msex_versions: Cow::Owned(versions),
+            supported_library_types: library_types,
+            thumbnail_formats_count: formats.len() as _,
+            thumbnail_formats: Cow::Owned(formats.clone()),
+            stream_formats_count: formats.len() as _,
+            stream_formats: Cow::Owned(formats),
+            layer_count: layer_dmx_sources.len() as _,
+            layer_dmx_sources: Cow::Owned(layer_dmx_sources),
+        };
+        if version < Version::V1_2 {
+            sinf.supported_msex_versions_count = 0;
+            sinf.supported_msex_versions = Cow::Owned(vec![]);
+            sinf.thumbnail_formats_count = 0;
+            sinf.thumbnail_formats = Cow::Owned(vec![]);
+            sinf.stream_formats_count = 0;
+            sinf.stream_formats = Cow::Owned(vec![]);
+        }
+        sinf
+    }
+}
+
+impl<'a> Connection<'a> {
+    fn run(&mut self) -> Result<()> {
+        loop {
+            match self.session.recv_bytes() {
+                // The whole message was consumed, so a message that cannot be decoded is refused.
+                Ok(bytes) => match Packet::decode(&bytes) {
+                    Ok(packet) => self.handle(packet)?,
+                    Err(_) => self.refuse(&bytes)?,
+                },
+                Err(Error::Io(err)) => match err.kind() {
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => (),
+                    io::ErrorKind::UnexpectedEof | io::ErrorKind::ConnectionReset => return Ok(()),
+                    _ => return Err(err.into()),
+                },
+                // The session cannot recover from an invalid base header.
+                Err(err) => return Err(err),
+            }
+            self.poll(Instant::now())?;
+        }
+    }
+
+    /// Perform the legacy handshake, send LSta messages and stream frames as required.
+    fn poll(&mut self, now: Instant) -> Result<()> {
+        if self.negotiator.version().is_none() {
+            if now.duration_since(self.connected) < MockMediaServer::HANDSHAKE_TIMEOUT {
+                return Ok(());
+            }
+            self.legacy_handshake()?;
+        }
+        let version = match self.negotiator.version() {
+            Some(version) => version,
+            None => return Ok(()),
+        };
+
+        let lsta_due = match self.last_lsta {
+            None => true,
+            Some(last) => now.duration_since(last) >= self.server.lsta_interval,
+        };
+        if lsta_due {
+            let elapsed = now.duration_since(self.connected);
+            let layers: Vec<LayerStatus> = self
+                .server
+                .layers
+                .iter()
+                .map(|layer| advance(layer, elapsed))
+                .collect();
+            let lsta = LSta {
+                layer_count: layers.len() as _,
+                layers: Cow::Owned(layers),
+            };
+            self.send(self.negotiator.message(lsta))?;
+            self.last_lsta = Some(now);
+        }
+
+        self.streams.retain(|stream| now < stream.deadline);
+        for i in 0..self.streams.len() {
+            if now < self.streams[i].next_frame {
+                continue;
+            }
+            let stream = &mut self.streams[i];
+            stream.next_frame = (stream.next_frame + stream.interval).max(now);
+            let source = self
+                .server
+                .sources
+                .iter()
+                .find(|source| source.identifier == stream.source_identifier)
+                .expect("streams are only started for known sources");
+            let frame_buffer = solid_rgb8(version, source.colour, stream.width, stream.height);
+            let stfr = StFr {
+                media_server_uuid: self.server.uuid,
+                source_identifier: stream.source_identifier,
+                frame_format: StreamFormat::Rgb8,
+                frame_width: stream.width,
+                frame_height: stream.height,
+                frame_buffer_size: frame_buffer.len() as _,
+                frame_buffer: Cow::Owned(frame_buffer),
+            };
+            let mut bytes = vec![];
+            bytes.write_bytes(self.negotiator.message(stfr))?;
+            self.socket.send_to(&bytes, self.server.stream_addr)?;
+        }
+        Ok(())
+    }
+
+    /// Send an SInf message using the highest supported version prior to MSEX 1.2, if any.
+    fn legacy_handshake(&mut self) -> Result<()> {
+        if let Some(version) = self.negotiator.select_legacy_version() {
+            self.send(self.negotiator.message(self.server.sinf(version)))?;
+        }
+        Ok(())
+    }
+
+    /// Refuse a message that could not be decoded, if its base and MSEX headers are readable.
+    fn refuse(&mut self, mut bytes: &[u8]) -> Result<()> {
+        match bytes.read_bytes::<super::Header>() {
+            Ok(header) => self.nack(&header.citp_header, header.content_type),
+            Err(_) => Ok(()),
+        }
+    }
+
+    fn handle(&mut self, packet: Packet) -> Result<()> {
+        let msg = match packet {
+            Packet::Msex(msg) => msg,
+            Packet::Unknown { layer, cookie, .. } if layer == super::Header::COOKIE => {
+                let citp_header = packet.citp_header()?;
+                return self.nack(&citp_header, cookie.get());
+            }
+            _ => return Ok(()),
+        };
+
+        if let MsexMessage::CInf(ref cinf) = msg {
+            return self.receive_cinf(&cinf.message);
+        }
+        if self.negotiator.version().is_none() {
+            self.legacy_handshake()?;
+        }
+        let version = match self.negotiator.version() {
+            Some(version) => version,
+            None => return Ok(()),
+        };
+        let header = msg.msex_header();
+        if self.negotiator.check(&header).is_err() {
+            return self.nack(&header.citp_header, header.content_type);
+        }
+        let kind = match header.citp_header.kind.request_index() {
+            Some(index) => Kind::response(index),
+            None => Kind::IGNORED,
+        };
+
+        match msg {
+            MsexMessage::GELI(ref msg) => self.geli(version, kind, &msg.message),
+            MsexMessage::GEIn(ref msg) => match self.gein(version, kind, &msg.message)? {
+                true => Ok(()),
+                false => self.nack(&header.citp_header, header.content_type),
+            },
+            MsexMessage::GETh(ref msg) => match self.geth(version, kind, &msg.message)? {
+                true => Ok(()),
+                false => self.nack(&header.citp_header, header.content_type),
+            },
+            MsexMessage::GELT(ref msg) if version >= Version::V1_1 => {
+                match self.gelt(version, kind, &msg.message)? {
+                    true => Ok(()),
+                    false => self.nack(&header.citp_header, header.content_type),
+                }
+            }
+            MsexMessage::GVSr(_) => {
+                let sources: Vec<SourceInformation> = self
+                    .server
+                    .sources
+                    .iter()
+                    .map(|source| SourceInformation {
+                        source_identifier: source.identifier,
+                        source_name: ucs2(&source.name),
+                        physical_output: 0,
+                        layer_number: SourceInformation::NONE,
+                        flags: 0,
+                        width: source.width,
+                        height: source.height,
+                    })
+                    .collect();
+                let vsrc = VSrc {
+                    source_count: sources.len() as _,
+                    sources: Cow::Owned(sources),
+                };
+                self.respond(kind, vsrc)
+            }
+            MsexMessage::RqSt(ref msg) => match self.rqst(&msg.message) {
+                true => Ok(()),
+                false => self.nack(&header.citp_header, header.content_type),
+            },
+            // Never respond to a Nack with a Nack.
+            MsexMessage::Nack(_) => Ok(()),
+            _ => self.nack(&header.citp_header, header.content_type),
+        }
+    }
+
+    fn receive_cinf(&mut self, cinf: &CInf) -> Result<()> {
+        match self.negotiator.receive_cinf(cinf) {
+            Ok(version) => self.send(self.negotiator.message(self.server.sinf(version))),
+            Err(Error::NoCommonVersion) if self.negotiator.version().is_none() => {
+                self.legacy_handshake()
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn geli(&mut self, version: Version, kind: Kind, geli: &GELI) -> Result<()> {
+        let parent_id = if version < Version::V1_1 {
+            LibraryId::ROOT
+        } else {
+            geli.library_parent_id
+        };
+        let libraries: Vec<LibraryInformation> = self
+            .server
+            .libraries
+            .iter()
+            .filter(|library| library.library_type == geli.library_type)
+            .filter(|library| library.id.parent() == Some(parent_id))
+            .filter(|library| {
+                geli.library_numbers.is_empty()
+                    || geli
+                        .library_numbers
+                        .contains(&library.id.number_within_parent())
+            })
+            .map(|library| self.library_information(library))
+            .collect();
+        let elin = ELIn {
+            library_type: geli.library_type,
+            library_count: libraries.len() as _,
+            libraries: Cow::Owned(libraries),
+        };
+        self.respond(kind, elin)
+    }
+
+    /// Returns `false` if the library does not exist or its information cannot be sent.
+    fn gein(&mut self, version: Version, kind: Kind, gein: &GEIn) -> Result<bool> {
+        let library = match self.server.library(gein.library_type, gein.library_id) {
+            Some(library) => library,
+            None => return Ok(false),
+        };
+        let elements: Vec<&MockElement> = library
+            .elements
+            .iter()
+            .filter(|element| {
+                gein.element_numbers.is_empty() || gein.element_numbers.contains(&element.number)
+            })
+            .collect();
+        let library_id = library.id;
+        match library.library_type {
+            LibraryType::Media => {
+                let elements: Vec<MediaInformation> = elements
+                    .iter()
+                    .map(|element| MediaInformation {
+                        number: element.number,
+                        serial_number: 0,
+                        dmx_range_min: element.number,
+                        dmx_range_max: element.number,
+                        media_name: ucs2(&element.name),
+                        media_version_timestamp: 0,
+                        media_width: 1920,
+                        media_height: 1080,
+                        media_length: 300,
+                        media_fps: 30,
+                    })
+                    .collect();
+                let mein = MEIn {
+                    library_id,
+                    element_count: elements.len() as _,
+                    elements: Cow::Owned(elements),
+                };
+                self.respond(kind, mein)?;
+            }
+            LibraryType::Effects => {
+                let effects: Vec<EffectInformation> = elements
+                    .iter()
+                    .map(|element| EffectInformation {
+                        element_number: element.number,
+                        serial_number: 0,
+                        dmx_range_min: element.number,
+                        dmx_range_max: element.number,
+                        effect_name: ucs2(&element.name),
+                        effect_parameter_count: 0,
+                        effect_parameter_names: Cow::Owned(vec![]),
+                    })
+                    .collect();
+                let eein = EEIn {
+                    library_id,
+                    effect_count: effects.len() as _,
+                    effects: Cow::Owned(effects),
+                };
+                self.respond(kind, eein)?;
+            }
+            library_type => {
+                if version < Version::V1_1 {
+                    return Ok(false);
+                }
+                let elements: Vec<GenericInformation> = elements
+                    .iter()
+                    .map(|element| GenericInformation {
+                        element_number: element.number,
+                        serial_number: 0,
+                        dmx_range_min: element.number,
+                        dmx_range_max: element.number,
+                        name: ucs2(&element.name),
+                        version_timestamp: 0,
+                    })
+                    .collect();
+                let glei = GLEI {
+                    library_type,
+                    library_id,
+                    element_count: elements.len() as _,
+                    elements: Cow::Owned(elements),
+                };
+                self.respond(kind, glei)?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Returns `false` if the library does not exist.
+    fn geth(&mut self, version: Version, kind: Kind, geth: &GETh) -> Result<bool> {
+        let library = match self.server.library(geth.library_type, geth.library_id) {
+            Some(library) => library,
+            None => return Ok(false),
+        };
+        let (width, height) = image_size(geth.thumbnail_width, geth.thumbnail_height);
+        for element in library.elements.iter() {
+            if !geth.element_numbers.is_empty() && !geth.element_numbers.contains(&element.number) {
+                continue;
+            }
+            let buffer = solid_rgb8(version, element.colour, width, height);
+            let ethn = EThn {
+                library_type: library.library_type,
+                library_id: library.id,
+                element_number: element.number,
+                thumbnail_format: ThumbnailFormat::Rgb8,
+                thumbnail_width: width,
+                thumbnail_height: height,
+                thumbnail_buffer_size: buffer.len() as _,
+                thumbnail_buffer: Cow::Owned(buffer),
+            };
+            self.respond(kind, ethn)?;
+        }
+        Ok(true)
+    }
+
+    /// Returns `false` if any of the requested libraries does not exist.
+    fn gelt(&mut self, version: Version, kind: Kind, gelt: &GELT) -> Result<bool> {
+        let server = self.server;
+        let unknown = gelt
+            .library_ids
+            .iter()
+            .any(|&id| server.library(gelt.library_type, id).is_none());
+        if unknown {
+            return Ok(false);
+        }
+        let (width, height) = image_size(gelt.thumbnail_width, gelt.thumbnail_height);
+        for library in server.libraries.iter() {
+            let requested = library.library_type == gelt.library_type
+                && (gelt.library_ids.is_empty() || gelt.library_ids.contains(&library.id));
+            if !requested {
+                continue;
+            }
+            let buffer = solid_rgb8(version, library.colour, width, height);
+            let elth = ELTh {
+                library_type: library.library_type,
+                library_id: library.id,
+                thumbnail_format: ThumbnailFormat::Rgb8,
+                thumbnail_width: width,
+                thumbnail_height: height,
+                thumbnail_buffer_size: buffer.len() as _,
+                thumbnail_buffer: Cow::Owned(buffer),
+            };
+            self.respond(kind, elth)?;
+        }
+        Ok(true)
+    }
+
+    /// Start or renew a stream. Returns `false` if the source or format is not supported.
+    fn rqst(&mut self, rqst: &RqSt) -> bool {
+        let source = self
+            .server
+            .sources
+            .iter()
+            .find(|source| source.identifier == rqst.source_identifier);
+        if source.is_none() || rqst.frame_format != StreamFormat::Rgb8 {
+            return false;
+        }
+        let now = Instant::now();
+        let (width, height) = image_size(rqst.frame_width, rqst.frame_height);
+        let stream = Stream {
+            source_identifier: rqst.source_identifier,
+            width,
+            height,
+            interval: Duration::from_secs(1) / rqst.fps.max(1) as u32,
+            next_frame: now,
+            deadline: now + Duration::from_secs(rqst.timeout as u64),
+        };
+        self.streams
+            .retain(|s| s.source_identifier != rqst.source_identifier);
+        self.streams.push(stream);
+        true
+    }
+
+    fn library_information(&self, library: &MockLibrary) -> LibraryInformation {
+        let sub_libraries = self
+            .server
+            .libraries
+            .iter()
+            .filter(|l| l.library_type == library.library_type)
+            .filter(|l| l.id.parent() == Some(library.id))
+            .count();
+        let number = library.id.number_within_parent();
+        LibraryInformation {
+            id: library.id,
+            serial_number: 0,
+            dmx_range_min: number,
+            dmx_range_max: number,
+            name: ucs2(&library.name),
+            library_count: sub_libraries as _,
+            element_count: library.elements.len() as _,
+        }
+    }
+
+    /// Refuse the message with the given content type. Nack requires MSEX 1.2.
+    fn nack(&mut self, citp_header: &protocol::Header, content_type: u32) -> Result<()> {
+        match self.negotiator.version() {
+            Some(version) if version >= Version::V1_2 => (),
+            _ => return Ok(()),
+        }
+        let kind = match citp_header.kind.request_index() {
+            Some(index) => Kind::response(index),
+            None => Kind::IGNORED,
+        };
+        let nack = Nack {
+            received_content_type: content_type,
+        };
+        self.respond(kind, nack)
+    }
+
+    /// Send a message of the negotiated version.
+    fn respond<T>(&mut self, kind: Kind, message: T) -> Result<()>
+    where
+        T: ContentType + SizeBytesVersioned + WriteToBytesVersioned,
+    {
+        let mut msg = self.negotiator.message(message);
+        msg.msex_header.citp_header.kind = kind;
+        self.send(msg)
+    }
+
+    fn send<T: WriteToBytesVersioned>(&mut self, msg: Message<T>) -> Result<()> {
+        self.session.send(&msg)
+    }
+}
+
+/// The status of the layer after the given time has elapsed since connecting.
+fn advance(layer: &LayerStatus, elapsed: Duration) -> LayerStatus {
+    let mut layer = layer.clone();
+    let playing = layer.layer_status_flags & LayerStatus::MEDIA_PLAYING != 0;
+    if playing && layer.media_length > 0 {
+        let frames = elapsed.as_secs_f64() * layer.media_fps as f64;
+        layer.media_position = (frames as u64 % layer.media_length as u64) as u32;
+    }
+    layer
+}
+
+/// Clamp the requested image size so that an RGB8 image fits within `MAX_IMAGE_BYTES`.
+fn image_size(width: u16, height: u16) -> (u16, u16) {
+    let (mut width, mut height) = match (width, height) {
+        (0, _) | (_, 0) => DEFAULT_IMAGE_SIZE,
+        size => size,
+    };
+    while width as usize * height as usize * 3 > MAX_IMAGE_BYTES {
+        width = (width / 2).max(1);
+        height = (height / 2).max(1);
+    }
+    (width, height)
+}
+
+/// An RGB8 image of a single colour, in BGR byte order for MSEX 1.0.
+fn solid_rgb8(version: Version, colour: [u8; 3], width: u16, height: u16) -> Vec<u8> {
+    let mut buffer = colour.repeat(width as usize * height as usize);
+    if version < Version::V1_1 {
+        bgr_to_rgb(&mut buffer);
+    }
+    buffer
+}
+
+fn ucs2(s: &str) -> Ucs2 {
+    s.parse().expect("failed to encode the string as UCS-2")
+}
+
+fn default_layer() -> LayerStatus {
+    LayerStatus {
+        layer_number: 0,
+        physical_output: 0,
+        media_library_type: LibraryType::Media,
+        media_library_id: LibraryId::from_number(1),
+        media_number: 1,
+        media_name: ucs2("Red"),
+        media_position: 0,
+        media_length: 300,
+        media_fps: 30,
+        layer_status_flags: LayerStatus::MEDIA_PLAYING,
+    }
+}
+
+fn default_libraries() -> Vec<MockLibrary> {
+    let element = |number, name: &str, colour| MockElement {
+        number,
+        name: name.to_string(),
+        colour,
+    };
+    vec![
+        MockLibrary {
+            library_type: LibraryType::Media,
+            id: LibraryId::from_number(1),
+            name: "Media".to_string(),
+            colour: [128, 128, 128],
+            elements: vec![
+                element(1, "Red", [255, 0, 0]),
+                element(2, "Green", [0, 255, 0]),
+                element(3, "Blue", [0, 0, 255]),
+            ],
+        },
+        MockLibrary {
+            library_type: LibraryType::Effects,
+            id: LibraryId::from_number(1),
+            name: "Effects".to_string(),
+            colour: [64, 64, 64],
+            elements: vec![
+                element(1, "Blur", [255, 255, 0]),
+                element(2, "Invert", [0, 255, 255]),
+            ],
+        },
+    ]
+}
@@ -0,0 +1,212 @@
+use super::{StFr, StreamFormat, Uuid};
+use crate::{Error, Result};
+use std::collections::HashMap;
+use std::mem;
+
+/// Reassembles video frames from MSEX StFr messages.
+///
+/// MSEX 1.2 introduces the "fJPG" and "fPNG" stream formats in which a single frame is split
+/// across multiple StFr messages, each beginning with a `FragmentHeader`. The assembler collects
+/// these fragments per stream - identified by the media server UUID and the source identifier -
+/// and yields each frame once all of its fragments have been received. This is independent of the
+/// `message_part` mechanism of the base header.
+///
+/// As StFr messages are sent over UDP, fragments may be lost or arrive out of order. Fragments
+/// may arrive in any order within a frame, but a partially received frame is dropped as soon as
+/// a fragment of a newer frame arrives for the same stream. Fragments of older frames are ignored.
+///
+/// Unfragmented frames are yielded immediately.
+///
+/// The size of a fragmented frame is determined by the byte offsets chosen by the sender. Frames
+/// are buffered up to `max_frame_size` bytes and fragments extending beyond it are rejected.
+#[derive(Clone, Debug)]
+pub struct FrameAssembler {
+    streams: HashMap<(Uuid, u16), PartialFrame>,
+    max_frame_size: usize,
+}
+
+/// A complete, encoded video frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Frame {
+    /// The UUID of the media server that sent the frame. Zeroed prior to MSEX 1.2.
+    pub media_server_uuid: Uuid,
+    /// The identifier of the source from which the frame originates.
+    pub source_identifier: u16,
+    /// The index of the frame within the stream. `None` for unfragmented formats.
+    pub frame_index: Option<u32>,
+    /// The format of `data`. Fragmented formats yield `Jpeg` or `Png`.
+    pub format: StreamFormat,
+    /// The width of the frame.
+    pub width: u16,
+    /// The height of the frame.
+    pub height: u16,
+    /// The encoded image data.
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Debug)]
+struct PartialFrame {
+    frame_index: u32,
+    format: StreamFormat,
+    width: u16,
+    height: u16,
+    received: Vec<bool>,
+    remaining: usize,
+    data: Vec<u8>,
+}
+
+impl FrameAssembler {
+    /// The default maximum size of a reassembled frame in bytes.
+    pub const DEFAULT_MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+    /// Create an assembler with no partially received frames.
+    pub fn new() -> Self {
+        FrameAssembler {
+            streams: HashMap::new(),
+            max_frame_size: Self::DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+
+    /// Specify the maximum size of a reassembled frame in bytes.
+    ///
+    /// Pushing a fragment that extends beyond `max_frame_size` produces a `FrameTooLarge` error
+    /// rather than buffering the fragment.
+    pub fn with_max_frame_size(mut self, max_frame_size: usize) -> Self {
+        self.max_frame_size = max_frame_size;
+        self
+    }
+
+    /// The maximum size of a reassembled frame in bytes.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    /// Add the frame or fragment carried by the given StFr message.
+    ///
+    /// Returns the frame if it is now complete. Duplicate fragments and fragments of stale frames
+    /// are ignored. Returns an error if the fragment header is missing or invalid, or if the
+    /// fragment extends beyond the maximum frame size.
+    pub fn push(&mut self, stfr: &StFr) -> Result<Option<Frame>> {
+        let (header, fragment) = match stfr.fragment()? {
+            Some(fragment) => fragment,
+            None => {
+                let frame = Frame {
+                    media_server_uuid: stfr.media_server_uuid,
+                    source_identifier: stfr.source_identifier,
+                    frame_index: None,
+                    format: stfr.frame_format,
+                    width: stfr.frame_width,
+                    height: stfr.frame_height,
+                    data: stfr.frame_buffer.to_vec(),
+                };
+                return Ok(Some(frame));
+            }
+        };
+
+        let invalid_fragment = Error::InvalidFragment {
+            frame_index: header.frame_index,
+            fragment_index: header.fragment_index,
+            fragment_count: header.fragment_count,
+        };
+        let fragment_count = header.fragment_count as usize;
+        let fragment_index = header.fragment_index as usize;
+        if fragment_index >= fragment_count {
+            return Err(invalid_fragment);
+        }
+        let start = header.fragment_byte_offset as usize;
+        let end = start + fragment.len();
+        if end > self.max_frame_size {
+            return Err(Error::FrameTooLarge {
+                size: end,
+                max: self.max_frame_size,
+            });
+        }
+
+        let key = (stfr.media_server_uuid, stfr.source_identifier);
+        let new_frame = || PartialFrame {
+            frame_index: header.frame_index,
+            format: unfragmented(stfr.frame_format),
+            width: stfr.frame_width,
+            height: stfr.frame_height,
+            received: vec![false; fragment_count],
+            remaining: fragment_count,
+            data: vec![],
+        };
+        let partial = self.streams.entry(key).or_insert_with(new_frame);
+        if partial.frame_index != header.frame_index {
+            if !is_newer(header.frame_index, partial.frame_index) {
+                return Ok(None);
+            }
+            *partial = new_frame();
+        }
+        if partial.received.len() != fragment_count {
+            return Err(invalid_fragment);
+        }
+        if partial.received[fragment_index] {
+            return Ok(None);
+        }
+
+        partial.received[fragment_index] = true;
+        partial.remaining -= 1;
+        if partial.data.len() < end {
+            partial.data.resize(end, 0);
+        }
+        partial.data[start..end].copy_from_slice(fragment);
+        if partial.remaining > 0 {
+            return Ok(None);
+        }
+
+        // Retain the completed frame's index so that late duplicates are ignored.
+        let frame = Frame {
+            media_server_uuid: stfr.media_server_uuid,
+            source_identifier: stfr.source_identifier,
+            frame_index: Some(partial.frame_index),
+            format: partial.format,
+            width: partial.width,
+            height: partial.height,
+            data: mem::take(&mut partial.data),
+        };
+        Ok(Some(frame))
+    }
+
+    /// Forget the state of the given stream, e.g. after it has timed out.
+    pub fn remove(&mut self, media_server_uuid: &Uuid, source_identifier: u16) {
+        self.streams
+            .remove(&(*media_server_uuid, source_identifier));
+    }
+
+    /// Forget the state of all streams.
+    pub fn clear(&mut self) {
+        self.streams.clear();
+    }
+
+    /// The number of streams from which fragments have been received.
+    pub fn len(&self) -> usize {
+        self.streams.len()
+    }
+
+    /// Whether or not no fragments have been received.
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+}
+
+impl Default for FrameAssembler {
+    fn default() -> Self {
+        FrameAssembler::new()
+    }
+}
+
+/// The format of the data carried by the fragments of the given format.
+fn unfragmented(format: StreamFormat) -> StreamFormat {
+    match format {
+        StreamFormat::FragmentedJpeg => StreamFormat::Jpeg,
+        StreamFormat::FragmentedPng => StreamFormat::Png,
+        format => format,
+    }
+}
+
+/// Whether frame index `a` follows `b`, allowing for the index wrapping around.
+fn is_newer(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) > 0
+}
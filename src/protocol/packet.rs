@@ -0,0 +1,605 @@
+use crate::protocol::{
+    caex, finf, fptc, fsel, msex, pinf, sdmx, ContentType, Cookie, Header, ReadBytes,
+    ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt, WriteToBytes,
+};
+use crate::{Error, Result};
+
+/// Any CITP message, decoded from a complete packet of little-endian bytes.
+///
+/// Each known second layer has its own enum describing all messages defined by the layer.
+/// Messages whose layer or message cookie is not recognised are preserved as `Unknown`, allowing
+/// them to be forwarded or silently discarded as the specification recommends.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Packet {
+    Pinf(PinfMessage),
+    Sdmx(SdmxMessage),
+    Fptc(FptcMessage),
+    Fsel(FselMessage),
+    Finf(FinfMessage),
+    Msex(MsexMessage),
+    Caex(CaexMessage),
+    /// A message with an unrecognised layer or message cookie.
+    Unknown {
+        /// The content type of the base header, identifying the second layer.
+        layer: Cookie,
+        /// The cookie identifying the message within the layer.
+        ///
+        /// For unknown layers this is the first four bytes following the base header, as is the
+        /// convention for most layers. `Cookie(0)` if the message is too short to contain a cookie.
+        cookie: Cookie,
+        /// The raw bytes of the entire message, including all headers.
+        payload: Vec<u8>,
+    },
+}
+
+/// All messages of the PINF layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PinfMessage {
+    PNam(pinf::Message<pinf::PNam>),
+    PLoc(pinf::Message<pinf::PLoc>),
+}
+
+/// All messages of the SDMX layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum SdmxMessage {
+    Capa(sdmx::Message<sdmx::Capa<'static>>),
+    UNam(sdmx::Message<sdmx::UNam>),
+    EnId(sdmx::Message<sdmx::EnId>),
+    ChBk(sdmx::Message<sdmx::ChBk<'static>>),
+    ChLs(sdmx::Message<sdmx::ChLs<'static>>),
+    SXSr(sdmx::Message<sdmx::SXSr>),
+    Sxus(sdmx::Message<sdmx::Sxus>),
+}
+
+/// All messages of the FPTC layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FptcMessage {
+    Ptch(fptc::Message<fptc::Ptch>),
+    UPtc(fptc::Message<fptc::UPtc<'static>>),
+    SPtc(fptc::Message<fptc::SPtc<'static>>),
+}
+
+/// All messages of the FSEL layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FselMessage {
+    Sele(fsel::Message<fsel::Sele<'static>>),
+    DeSe(fsel::Message<fsel::DeSe<'static>>),
+}
+
+/// All messages of the FINF layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum FinfMessage {
+    SFra(finf::Message<finf::SFra<'static>>),
+    Fram(finf::Message<finf::Fram>),
+}
+
+/// All messages of the MSEX layer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MsexMessage {
+    CInf(msex::Message<msex::CInf<'static>>),
+    SInf(msex::Message<msex::SInf<'static>>),
+    Nack(msex::Message<msex::Nack>),
+    LSta(msex::Message<msex::LSta<'static>>),
+    GELI(msex::Message<msex::GELI<'static>>),
+    ELIn(msex::Message<msex::ELIn<'static>>),
+    ELUp(msex::Message<msex::ELUp>),
+    GEIn(msex::Message<msex::GEIn<'static>>),
+    MEIn(msex::Message<msex::MEIn<'static>>),
+    EEIn(msex::Message<msex::EEIn<'static>>),
+    GLEI(msex::Message<msex::GLEI<'static>>),
+    GETh(msex::Message<msex::GETh<'static>>),
+    EThn(msex::Message<msex::EThn<'static>>),
+    GELT(msex::Message<msex::GELT<'static>>),
+    ELTh(msex::Message<msex::ELTh<'static>>),
+    GVSr(msex::Message<msex::GVSr>),
+    VSrc(msex::Message<msex::VSrc<'static>>),
+    RqSt(msex::Message<msex::RqSt>),
+    StFr(msex::Message<msex::StFr<'static>>),
+}
+
+/// All messages of the CAEX layer.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CaexMessage {
+    Nack(caex::Message<caex::Nack>),
+    EnterShow(caex::Message<caex::EnterShow>),
+    LeaveShow(caex::Message<caex::LeaveShow>),
+    FixtureListRequest(caex::Message<caex::FixtureListRequest>),
+    FixtureList(caex::Message<caex::FixtureList<'static>>),
+    FixtureRemove(caex::Message<caex::FixtureRemove<'static>>),
+    FixtureConsoleStatus(caex::Message<caex::FixtureConsoleStatus<'static>>),
+    GetLaserFeedList(caex::Message<caex::GetLaserFeedList>),
+    LaserFeedList(caex::Message<caex::LaserFeedList<'static>>),
+    LaserFeedControl(caex::Message<caex::LaserFeedControl>),
+    LaserFeedFrame(caex::Message<caex::LaserFeedFrame<'static>>),
+}
+
+impl Packet {
+    /// Decode a single message from the start of the given bytes.
+    ///
+    /// The slice must contain at least the number of bytes described by the `message_size` field
+    /// of the base header. Any bytes following the message are ignored. Known messages must
+    /// occupy exactly `message_size` bytes, otherwise a `MessageSizeMismatch` error is returned.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let citp_header: Header = (&bytes[..]).read_bytes()?;
+        let message_size = citp_header.message_size as usize;
+        if bytes.len() < message_size {
+            return Err(Error::Truncated {
+                expected: message_size,
+                actual: bytes.len(),
+            });
+        }
+        if message_size < citp_header.size_bytes() {
+            return Err(Error::Truncated {
+                expected: citp_header.size_bytes(),
+                actual: message_size,
+            });
+        }
+        let bytes = &bytes[..message_size];
+        match Cookie(citp_header.content_type) {
+            pinf::Header::COOKIE => decode_pinf(bytes),
+            sdmx::Header::COOKIE => decode_sdmx(bytes),
+            fptc::Header::COOKIE => decode_fptc(bytes),
+            fsel::Header::COOKIE => decode_fsel(bytes),
+            finf::Header::COOKIE => decode_finf(bytes),
+            msex::Header::COOKIE => decode_msex(bytes),
+            caex::Header::COOKIE => decode_caex(bytes),
+            _ => {
+                let reader = &bytes[citp_header.size_bytes()..];
+                let cookie = match reader.len() {
+                    n if n >= 4 => u32::from_le_bytes([reader[0], reader[1], reader[2], reader[3]]),
+                    _ => 0,
+                };
+                Ok(unknown(citp_header, cookie, bytes))
+            }
+        }
+    }
+
+    /// Produce an error for `Unknown` packets, returning all other packets unchanged.
+    ///
+    /// Useful for peers that treat unrecognised messages as an error rather than silently
+    /// discarding them.
+    pub fn known(self) -> Result<Self> {
+        match self {
+            Packet::Unknown { layer, cookie, .. } => match layer {
+                pinf::Header::COOKIE
+                | sdmx::Header::COOKIE
+                | fptc::Header::COOKIE
+                | fsel::Header::COOKIE
+                | finf::Header::COOKIE
+                | msex::Header::COOKIE
+                | caex::Header::COOKIE => Err(Error::UnknownMessage { layer, cookie }),
+                _ => Err(Error::UnknownLayer { layer }),
+            },
+            packet => Ok(packet),
+        }
+    }
+
+    /// Encode the message to a new buffer of little-endian bytes.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut bytes = vec![];
+        self.write_to_bytes(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// The base header of the message.
+    ///
+    /// The header of an `Unknown` packet is read from its payload. Returns an error if the payload
+    /// does not begin with a valid base header, which is never the case for packets produced by
+    /// `Packet::decode`.
+    pub fn citp_header(&self) -> Result<Header> {
+        let header = match *self {
+            Packet::Pinf(ref msg) => msg.citp_header(),
+            Packet::Sdmx(ref msg) => msg.citp_header(),
+            Packet::Fptc(ref msg) => msg.citp_header(),
+            Packet::Fsel(ref msg) => msg.citp_header(),
+            Packet::Finf(ref msg) => msg.citp_header(),
+            Packet::Msex(ref msg) => msg.citp_header(),
+            Packet::Caex(ref msg) => msg.citp_header(),
+            Packet::Unknown { ref payload, .. } => (&payload[..]).read_bytes()?,
+        };
+        Ok(header)
+    }
+
+    /// Whether or not the message is a negative acknowledgement of a request.
+    pub fn is_nack(&self) -> bool {
+        matches!(
+            *self,
+            Packet::Caex(CaexMessage::Nack(_)) | Packet::Msex(MsexMessage::Nack(_))
+        )
+    }
+}
+
+impl PinfMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            PinfMessage::PNam(ref msg) => msg.pinf_header.citp_header,
+            PinfMessage::PLoc(ref msg) => msg.pinf_header.citp_header,
+        }
+    }
+}
+
+impl SdmxMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            SdmxMessage::Capa(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::UNam(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::EnId(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::ChBk(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::ChLs(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::SXSr(ref msg) => msg.sdmx_header.citp_header,
+            SdmxMessage::Sxus(ref msg) => msg.sdmx_header.citp_header,
+        }
+    }
+}
+
+impl FptcMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            FptcMessage::Ptch(ref msg) => msg.fptc_header.citp_header,
+            FptcMessage::UPtc(ref msg) => msg.fptc_header.citp_header,
+            FptcMessage::SPtc(ref msg) => msg.fptc_header.citp_header,
+        }
+    }
+}
+
+impl FselMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            FselMessage::Sele(ref msg) => msg.fsel_header.citp_header,
+            FselMessage::DeSe(ref msg) => msg.fsel_header.citp_header,
+        }
+    }
+}
+
+impl FinfMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            FinfMessage::SFra(ref msg) => msg.finf_header.citp_header,
+            FinfMessage::Fram(ref msg) => msg.finf_header.citp_header,
+        }
+    }
+}
+
+impl MsexMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        self.msex_header().citp_header
+    }
+
+    /// The MSEX header of the message.
+    pub fn msex_header(&self) -> msex::Header {
+        match *self {
+            MsexMessage::CInf(ref msg) => msg.msex_header,
+            MsexMessage::SInf(ref msg) => msg.msex_header,
+            MsexMessage::Nack(ref msg) => msg.msex_header,
+            MsexMessage::LSta(ref msg) => msg.msex_header,
+            MsexMessage::GELI(ref msg) => msg.msex_header,
+            MsexMessage::ELIn(ref msg) => msg.msex_header,
+            MsexMessage::ELUp(ref msg) => msg.msex_header,
+            MsexMessage::GEIn(ref msg) => msg.msex_header,
+            MsexMessage::MEIn(ref msg) => msg.msex_header,
+            MsexMessage::EEIn(ref msg) => msg.msex_header,
+            MsexMessage::GLEI(ref msg) => msg.msex_header,
+            MsexMessage::GETh(ref msg) => msg.msex_header,
+            MsexMessage::EThn(ref msg) => msg.msex_header,
+            MsexMessage::GELT(ref msg) => msg.msex_header,
+            MsexMessage::ELTh(ref msg) => msg.msex_header,
+            MsexMessage::GVSr(ref msg) => msg.msex_header,
+            MsexMessage::VSrc(ref msg) => msg.msex_header,
+            MsexMessage::RqSt(ref msg) => msg.msex_header,
+            MsexMessage::StFr(ref msg) => msg.msex_header,
+        }
+    }
+}
+
+impl CaexMessage {
+    /// The base header of the message.
+    pub fn citp_header(&self) -> Header {
+        match *self {
+            CaexMessage::Nack(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::EnterShow(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::LeaveShow(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::FixtureListRequest(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::FixtureList(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::FixtureRemove(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::FixtureConsoleStatus(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::GetLaserFeedList(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::LaserFeedList(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::LaserFeedControl(ref msg) => msg.caex_header.citp_header,
+            CaexMessage::LaserFeedFrame(ref msg) => msg.caex_header.citp_header,
+        }
+    }
+}
+
+/// Read a whole message, validating its cookies and the `message_size` of its base header.
+fn read<T: ReadFromBytes>(mut bytes: &[u8]) -> Result<T> {
+    bytes.read_bytes()
+}
+
+fn unknown(citp_header: Header, cookie: u32, bytes: &[u8]) -> Packet {
+    Packet::Unknown {
+        layer: Cookie(citp_header.content_type),
+        cookie: Cookie(cookie),
+        payload: bytes.to_vec(),
+    }
+}
+
+fn decode_pinf(bytes: &[u8]) -> Result<Packet> {
+    let pinf_header: pinf::Header = read(bytes)?;
+    let msg = match Cookie(pinf_header.content_type) {
+        pinf::PNam::COOKIE => PinfMessage::PNam(read(bytes)?),
+        pinf::PLoc::COOKIE => PinfMessage::PLoc(read(bytes)?),
+        _ => {
+            let cookie = pinf_header.content_type;
+            return Ok(unknown(pinf_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Pinf(msg))
+}
+
+fn decode_sdmx(bytes: &[u8]) -> Result<Packet> {
+    let sdmx_header: sdmx::Header = read(bytes)?;
+    let msg = match Cookie(sdmx_header.content_type) {
+        sdmx::Capa::COOKIE => SdmxMessage::Capa(read(bytes)?),
+        sdmx::UNam::COOKIE => SdmxMessage::UNam(read(bytes)?),
+        sdmx::EnId::COOKIE => SdmxMessage::EnId(read(bytes)?),
+        sdmx::ChBk::COOKIE => SdmxMessage::ChBk(read(bytes)?),
+        sdmx::ChLs::COOKIE => SdmxMessage::ChLs(read(bytes)?),
+        sdmx::SXSr::COOKIE => SdmxMessage::SXSr(read(bytes)?),
+        sdmx::Sxus::COOKIE => SdmxMessage::Sxus(read(bytes)?),
+        _ => {
+            let cookie = sdmx_header.content_type;
+            return Ok(unknown(sdmx_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Sdmx(msg))
+}
+
+fn decode_fptc(bytes: &[u8]) -> Result<Packet> {
+    let fptc_header: fptc::Header = read(bytes)?;
+    let msg = match Cookie(fptc_header.content_type) {
+        fptc::Ptch::COOKIE => FptcMessage::Ptch(read(bytes)?),
+        fptc::UPtc::COOKIE => FptcMessage::UPtc(read(bytes)?),
+        fptc::SPtc::COOKIE => FptcMessage::SPtc(read(bytes)?),
+        _ => {
+            let cookie = fptc_header.content_type;
+            return Ok(unknown(fptc_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Fptc(msg))
+}
+
+fn decode_fsel(bytes: &[u8]) -> Result<Packet> {
+    let fsel_header: fsel::Header = read(bytes)?;
+    let msg = match Cookie(fsel_header.content_type) {
+        fsel::Sele::COOKIE => FselMessage::Sele(read(bytes)?),
+        fsel::DeSe::COOKIE => FselMessage::DeSe(read(bytes)?),
+        _ => {
+            let cookie = fsel_header.content_type;
+            return Ok(unknown(fsel_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Fsel(msg))
+}
+
+fn decode_finf(bytes: &[u8]) -> Result<Packet> {
+    let finf_header: finf::Header = read(bytes)?;
+    let msg = match Cookie(finf_header.content_type) {
+        finf::SFra::COOKIE => FinfMessage::SFra(read(bytes)?),
+        finf::Fram::COOKIE => FinfMessage::Fram(read(bytes)?),
+        _ => {
+            let cookie = finf_header.content_type;
+            return Ok(unknown(finf_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Finf(msg))
+}
+
+fn decode_msex(bytes: &[u8]) -> Result<Packet> {
+    let msex_header: msex::Header = read(bytes)?;
+    let msg = match Cookie(msex_header.content_type) {
+        msex::CInf::COOKIE => MsexMessage::CInf(read(bytes)?),
+        msex::SInf::COOKIE => MsexMessage::SInf(read(bytes)?),
+        msex::Nack::COOKIE => MsexMessage::Nack(read(bytes)?),
+        msex::LSta::COOKIE => MsexMessage::LSta(read(bytes)?),
+        msex::GELI::COOKIE => MsexMessage::GELI(read(bytes)?),
+        msex::ELIn::COOKIE => MsexMessage::ELIn(read(bytes)?),
+        msex::ELUp::COOKIE => MsexMessage::ELUp(read(bytes)?),
+        msex::GEIn::COOKIE => MsexMessage::GEIn(read(bytes)?),
+        msex::MEIn::COOKIE => MsexMessage::MEIn(read(bytes)?),
+        msex::EEIn::COOKIE => MsexMessage::EEIn(read(bytes)?),
+        msex::GLEI::COOKIE => MsexMessage::GLEI(read(bytes)?),
+        msex::GETh::COOKIE => MsexMessage::GETh(read(bytes)?),
+        msex::EThn::COOKIE => MsexMessage::EThn(read(bytes)?),
+        msex::GELT::COOKIE => MsexMessage::GELT(read(bytes)?),
+        msex::ELTh::COOKIE => MsexMessage::ELTh(read(bytes)?),
+        msex::GVSr::COOKIE => MsexMessage::GVSr(read(bytes)?),
+        msex::VSrc::COOKIE => MsexMessage::VSrc(read(bytes)?),
+        msex::RqSt::COOKIE => MsexMessage::RqSt(read(bytes)?),
+        msex::StFr::COOKIE => MsexMessage::StFr(read(bytes)?),
+        _ => {
+            let cookie = msex_header.content_type;
+            return Ok(unknown(msex_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Msex(msg))
+}
+
+fn decode_caex(bytes: &[u8]) -> Result<Packet> {
+    let caex_header: caex::Header = read(bytes)?;
+    let msg = match Cookie(caex_header.content_type) {
+        caex::Nack::COOKIE => CaexMessage::Nack(read(bytes)?),
+        caex::EnterShow::COOKIE => CaexMessage::EnterShow(read(bytes)?),
+        caex::LeaveShow::COOKIE => CaexMessage::LeaveShow(read(bytes)?),
+        caex::FixtureListRequest::COOKIE => CaexMessage::FixtureListRequest(read(bytes)?),
+        caex::FixtureList::COOKIE => CaexMessage::FixtureList(read(bytes)?),
+        caex::FixtureRemove::COOKIE => CaexMessage::FixtureRemove(read(bytes)?),
+        caex::FixtureConsoleStatus::COOKIE => CaexMessage::FixtureConsoleStatus(read(bytes)?),
+        caex::GetLaserFeedList::COOKIE => CaexMessage::GetLaserFeedList(read(bytes)?),
+        caex::LaserFeedList::COOKIE => CaexMessage::LaserFeedList(read(bytes)?),
+        caex::LaserFeedControl::COOKIE => CaexMessage::LaserFeedControl(read(bytes)?),
+        caex::LaserFeedFrame::COOKIE => CaexMessage::LaserFeedFrame(read(bytes)?),
+        _ => {
+            let cookie = caex_header.content_type;
+            return Ok(unknown(caex_header.citp_header, cookie, bytes));
+        }
+    };
+    Ok(Packet::Caex(msg))
+}
+
+impl From<PinfMessage> for Packet {
+    fn from(msg: PinfMessage) -> Self {
+        Packet::Pinf(msg)
+    }
+}
+
+impl From<SdmxMessage> for Packet {
+    fn from(msg: SdmxMessage) -> Self {
+        Packet::Sdmx(msg)
+    }
+}
+
+impl From<FptcMessage> for Packet {
+    fn from(msg: FptcMessage) -> Self {
+        Packet::Fptc(msg)
+    }
+}
+
+impl From<FselMessage> for Packet {
+    fn from(msg: FselMessage) -> Self {
+        Packet::Fsel(msg)
+    }
+}
+
+impl From<FinfMessage> for Packet {
+    fn from(msg: FinfMessage) -> Self {
+        Packet::Finf(msg)
+    }
+}
+
+impl From<MsexMessage> for Packet {
+    fn from(msg: MsexMessage) -> Self {
+        Packet::Msex(msg)
+    }
+}
+
+impl From<CaexMessage> for Packet {
+    fn from(msg: CaexMessage) -> Self {
+        Packet::Caex(msg)
+    }
+}
+
+impl WriteToBytes for Packet {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            Packet::Pinf(ref msg) => writer.write_bytes(msg),
+            Packet::Sdmx(ref msg) => writer.write_bytes(msg),
+            Packet::Fptc(ref msg) => writer.write_bytes(msg),
+            Packet::Fsel(ref msg) => writer.write_bytes(msg),
+            Packet::Finf(ref msg) => writer.write_bytes(msg),
+            Packet::Msex(ref msg) => writer.write_bytes(msg),
+            Packet::Caex(ref msg) => writer.write_bytes(msg),
+            Packet::Unknown { ref payload, .. } => Ok(writer.write_all(payload)?),
+        }
+    }
+}
+
+impl WriteToBytes for PinfMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            PinfMessage::PNam(ref msg) => writer.write_bytes(msg),
+            PinfMessage::PLoc(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for SdmxMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            SdmxMessage::Capa(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::UNam(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::EnId(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::ChBk(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::ChLs(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::SXSr(ref msg) => writer.write_bytes(msg),
+            SdmxMessage::Sxus(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for FptcMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            FptcMessage::Ptch(ref msg) => writer.write_bytes(msg),
+            FptcMessage::UPtc(ref msg) => writer.write_bytes(msg),
+            FptcMessage::SPtc(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for FselMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            FselMessage::Sele(ref msg) => writer.write_bytes(msg),
+            FselMessage::DeSe(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for FinfMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            FinfMessage::SFra(ref msg) => writer.write_bytes(msg),
+            FinfMessage::Fram(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for MsexMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            MsexMessage::CInf(ref msg) => writer.write_bytes(msg),
+            MsexMessage::SInf(ref msg) => writer.write_bytes(msg),
+            MsexMessage::Nack(ref msg) => writer.write_bytes(msg),
+            MsexMessage::LSta(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GELI(ref msg) => writer.write_bytes(msg),
+            MsexMessage::ELIn(ref msg) => writer.write_bytes(msg),
+            MsexMessage::ELUp(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GEIn(ref msg) => writer.write_bytes(msg),
+            MsexMessage::MEIn(ref msg) => writer.write_bytes(msg),
+            MsexMessage::EEIn(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GLEI(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GETh(ref msg) => writer.write_bytes(msg),
+            MsexMessage::EThn(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GELT(ref msg) => writer.write_bytes(msg),
+            MsexMessage::ELTh(ref msg) => writer.write_bytes(msg),
+            MsexMessage::GVSr(ref msg) => writer.write_bytes(msg),
+            MsexMessage::VSrc(ref msg) => writer.write_bytes(msg),
+            MsexMessage::RqSt(ref msg) => writer.write_bytes(msg),
+            MsexMessage::StFr(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
+
+impl WriteToBytes for CaexMessage {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        match *self {
+            CaexMessage::Nack(ref msg) => writer.write_bytes(msg),
+            CaexMessage::EnterShow(ref msg) => writer.write_bytes(msg),
+            CaexMessage::LeaveShow(ref msg) => writer.write_bytes(msg),
+            CaexMessage::FixtureListRequest(ref msg) => writer.write_bytes(msg),
+            CaexMessage::FixtureList(ref msg) => writer.write_bytes(msg),
+            CaexMessage::FixtureRemove(ref msg) => writer.write_bytes(msg),
+            CaexMessage::FixtureConsoleStatus(ref msg) => writer.write_bytes(msg),
+            CaexMessage::GetLaserFeedList(ref msg) => writer.write_bytes(msg),
+            CaexMessage::LaserFeedList(ref msg) => writer.write_bytes(msg),
+            CaexMessage::LaserFeedControl(ref msg) => writer.write_bytes(msg),
+            CaexMessage::LaserFeedFrame(ref msg) => writer.write_bytes(msg),
+        }
+    }
+}
@@ -1,9 +1,10 @@
 use crate::protocol::{
-    self, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt,
-    WriteToBytes, LE,
+    self, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes,
+    WriteBytesExt, WriteToBytes, LE,
 };
+use crate::Result;
 use std::ffi::CString;
-use std::{io, mem};
+use std::{fmt, mem};
 
 /// The old port originally used for broadcast.
 pub const OLD_BROADCAST_PORT: u16 = 4810;
@@ -64,7 +65,7 @@ pub struct PLoc {
     /// The port on which the peer is listening for incoming TCP connections. `0` if not listening.
     pub listening_tcp_port: u16,
     /// Can be "LightingConsole", "MediaServer" or "Visualiser".
-    pub kind: CString,
+    pub kind: PeerKind,
     /// The display name of the peer. Corresponds to the `pinf::PNam::name` field.
     pub name: CString,
     /// The display state of the peer. This can be descriptive string presentable to the user such
@@ -72,10 +73,40 @@ pub struct PLoc {
     pub state: CString,
 }
 
+/// The kind of peer described by the `PLoc::kind` field.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PeerKind {
+    LightingConsole,
+    MediaServer,
+    Visualiser,
+    /// A kind not described by the specification, preserved as it was received.
+    Other(CString),
+}
+
 impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"PINF";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a PINF message, filling in the CITP and PINF headers.
+    pub fn new(message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let pinf_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            pinf_header,
+            message,
+        };
+        msg.pinf_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl PNam {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"PNam";
 }
@@ -84,8 +115,62 @@ impl PLoc {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"PLoc";
 }
 
+impl PeerKind {
+    pub const LIGHTING_CONSOLE: &'static str = "LightingConsole";
+    pub const MEDIA_SERVER: &'static str = "MediaServer";
+    pub const VISUALISER: &'static str = "Visualiser";
+
+    /// The string written to the `PLoc::kind` field.
+    pub fn as_bytes(&self) -> &[u8] {
+        match *self {
+            PeerKind::LightingConsole => Self::LIGHTING_CONSOLE.as_bytes(),
+            PeerKind::MediaServer => Self::MEDIA_SERVER.as_bytes(),
+            PeerKind::Visualiser => Self::VISUALISER.as_bytes(),
+            PeerKind::Other(ref kind) => kind.as_bytes(),
+        }
+    }
+}
+
+impl From<CString> for PeerKind {
+    fn from(kind: CString) -> Self {
+        match kind.to_bytes() {
+            b if b == Self::LIGHTING_CONSOLE.as_bytes() => PeerKind::LightingConsole,
+            b if b == Self::MEDIA_SERVER.as_bytes() => PeerKind::MediaServer,
+            b if b == Self::VISUALISER.as_bytes() => PeerKind::Visualiser,
+            _ => PeerKind::Other(kind),
+        }
+    }
+}
+
+impl From<PeerKind> for CString {
+    fn from(kind: PeerKind) -> Self {
+        match kind {
+            PeerKind::Other(kind) => kind,
+            kind => CString::new(kind.as_bytes()).expect("known kinds contain no nul bytes"),
+        }
+    }
+}
+
+impl fmt::Display for PeerKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", String::from_utf8_lossy(self.as_bytes()))
+    }
+}
+
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for PNam {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for PLoc {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         Ok(())
@@ -96,7 +181,7 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.pinf_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
@@ -104,14 +189,14 @@ where
 }
 
 impl WriteToBytes for PNam {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(&self.name)?;
         Ok(())
     }
 }
 
 impl WriteToBytes for PLoc {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.listening_tcp_port)?;
         writer.write_bytes(&self.kind)?;
         writer.write_bytes(&self.name)?;
@@ -120,8 +205,47 @@ impl WriteToBytes for PLoc {
     }
 }
 
+impl WriteToBytes for PeerKind {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(self.as_bytes())?;
+        writer.write_u8(0)?;
+        Ok(())
+    }
+}
+
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let pinf_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, pinf_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&pinf_header.citp_header, reader.count())?;
+        let msg = Message {
+            pinf_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
 impl ReadFromBytes for PNam {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let name = reader.read_bytes()?;
         let pnam = PNam { name };
         Ok(pnam)
@@ -129,7 +253,7 @@ impl ReadFromBytes for PNam {
 }
 
 impl ReadFromBytes for PLoc {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let listening_tcp_port = reader.read_u16::<LE>()?;
         let kind = reader.read_bytes()?;
         let name = reader.read_bytes()?;
@@ -144,12 +268,40 @@ impl ReadFromBytes for PLoc {
     }
 }
 
+impl ReadFromBytes for PeerKind {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let kind: CString = reader.read_bytes()?;
+        Ok(kind.into())
+    }
+}
+
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.pinf_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
 impl SizeBytes for PNam {
     fn size_bytes(&self) -> usize {
         self.name.size_bytes()
     }
 }
 
+impl SizeBytes for PeerKind {
+    fn size_bytes(&self) -> usize {
+        self.as_bytes().len() + 1
+    }
+}
+
 impl SizeBytes for PLoc {
     fn size_bytes(&self) -> usize {
         mem::size_of::<u16>()
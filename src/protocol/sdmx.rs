@@ -1,10 +1,19 @@
 use crate::protocol::{
-    self, ReadBytes, ReadBytesExt, ReadFromBytes, SizeBytes, WriteBytes, WriteBytesExt,
-    WriteToBytes, LE,
+    self, ConstSizeBytes, ContentType, Cookie, ReadBytes, ReadBytesExt, ReadFromBytes, SdmxMessage,
+    SizeBytes, WriteBytes, WriteBytesExt, WriteToBytes, LE,
 };
+use crate::{Error, Result};
 use std::borrow::Cow;
 use std::ffi::CString;
-use std::{self, io, mem};
+use std::{fmt, mem};
+
+mod receiver;
+mod state;
+mod transmitter;
+
+pub use self::receiver::Receiver;
+pub use self::state::{Buffer, Changes, ChannelChange, DmxState};
+pub use self::transmitter::Transmitter;
 
 /// ## The SDMX header.
 ///
@@ -37,7 +46,7 @@ pub struct Message<T> {
 pub struct Capa<'a> {
     /// A list of capabilities.
     ///
-    /// See the `Caps` associated constants for possible capabilities.
+    /// See the `Capa` associated constants for possible capabilities.
     ///
     /// - 1   - ChLs channel list.
     /// - 2   - SXSr external source.
@@ -110,11 +119,11 @@ pub struct ChLs<'a> {
 #[repr(C)]
 pub struct ChannelLevel {
     /// `0`-based index of the universe.
-    universe_index: u8,
+    pub universe_index: u8,
     /// `0`-based index of the channel in the universe.
-    channel: u16,
+    pub channel: u16,
     /// DMX channel level.
-    channel_level: u8,
+    pub channel_level: u8,
 }
 
 /// ## SDMX / SXSr - Set External Source message.
@@ -136,7 +145,7 @@ pub struct SXSr {
     ///   channel of the first universe.
     /// - **ETC Net2**: "EtcNet2/<channel>", ie. "ETCNet2/1" is the first ETCNet2 channel.
     /// - **MA-Net**: "MANet/<type>/<universe>/<channel>", ie. "MANet/2/0/1" is the first channel
-    /// of the first MA-Net 2 universe.
+    ///   of the first MA-Net 2 universe.
     pub connection_string: CString,
 }
 
@@ -153,10 +162,50 @@ pub struct Sxus {
     pub connection_string: CString,
 }
 
+/// A set of the capabilities defined by the specification, e.g. `Capa::CHANNEL_LIST`.
+///
+/// Capabilities that are not defined by the specification have no meaning to this implementation
+/// and are ignored when reading a `Capa` message.
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    bits: u8,
+}
+
+/// Establishes the capabilities that may be used within a session.
+///
+/// Upon connecting, each peer sends a `Capa` message listing its own capabilities. Only the
+/// capabilities supported by both peers may be used. Until the remote peer's `Capa` message is
+/// received, no capabilities may be used.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CapabilityNegotiator {
+    local: Capabilities,
+    remote: Option<Capabilities>,
+}
+
 impl Header {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"SDMX";
 }
 
+impl<T> Message<T>
+where
+    T: ContentType + SizeBytes,
+{
+    /// Construct a SDMX message, filling in the CITP and SDMX headers.
+    pub fn new(message: T) -> Self {
+        let citp_header = protocol::Header::new(Header::COOKIE.get(), 0);
+        let sdmx_header = Header {
+            citp_header,
+            content_type: T::COOKIE.get(),
+        };
+        let mut msg = Message {
+            sdmx_header,
+            message,
+        };
+        msg.sdmx_header.citp_header.message_size = msg.size_bytes() as u32;
+        msg
+    }
+}
+
 impl<'a> Capa<'a> {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"Capa";
 
@@ -166,7 +215,18 @@ impl<'a> Capa<'a> {
     pub const ART_NET_EXTERNAL_SOURCES: u16 = 101;
     pub const BSR_E131_EXTERNAL_SOURCES: u16 = 102;
     pub const ETC_NET2_EXTERNAL_SOURCES: u16 = 103;
-    pub const MA_NET_EXTERNAL_SOURCES: u16 = 103;
+    pub const MA_NET_EXTERNAL_SOURCES: u16 = 104;
+
+    /// All capabilities defined by the specification.
+    pub const ALL: [u16; 7] = [
+        Self::CHANNEL_LIST,
+        Self::EXTERNAL_SOURCE,
+        Self::PER_UNIVERSE_EXTERNAL_SOURCES,
+        Self::ART_NET_EXTERNAL_SOURCES,
+        Self::BSR_E131_EXTERNAL_SOURCES,
+        Self::ETC_NET2_EXTERNAL_SOURCES,
+        Self::MA_NET_EXTERNAL_SOURCES,
+    ];
 }
 
 impl UNam {
@@ -193,8 +253,186 @@ impl Sxus {
     pub const CONTENT_TYPE: &'static [u8; 4] = b"SXUS";
 }
 
+impl Capabilities {
+    /// The set containing no capabilities.
+    pub const fn empty() -> Self {
+        Capabilities { bits: 0 }
+    }
+
+    /// The set containing all capabilities defined by the specification.
+    pub const fn all() -> Self {
+        Capabilities {
+            bits: (1 << Capa::ALL.len()) - 1,
+        }
+    }
+
+    /// Whether or not the set contains the given capability.
+    pub fn contains(&self, capability: u16) -> bool {
+        match bit(capability) {
+            Some(bit) => self.bits & bit != 0,
+            None => false,
+        }
+    }
+
+    /// Add a capability to the set.
+    ///
+    /// Returns `false` if the capability is not defined by the specification.
+    pub fn insert(&mut self, capability: u16) -> bool {
+        match bit(capability) {
+            Some(bit) => {
+                self.bits |= bit;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove a capability from the set.
+    pub fn remove(&mut self, capability: u16) {
+        if let Some(bit) = bit(capability) {
+            self.bits &= !bit;
+        }
+    }
+
+    /// The capabilities contained within both sets.
+    pub fn intersection(&self, other: &Self) -> Self {
+        Capabilities {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    /// The capabilities contained within either set.
+    pub fn union(&self, other: &Self) -> Self {
+        Capabilities {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    /// Whether or not the set contains no capabilities.
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+
+    /// The capabilities within the set in the order in which they are listed by `Capa::ALL`.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        Capa::ALL
+            .iter()
+            .cloned()
+            .filter(move |&capability| self.contains(capability))
+    }
+
+    /// The `Capa` message advertising the set.
+    pub fn capa(&self) -> Capa<'static> {
+        Capa {
+            capabilities: Cow::Owned(self.iter().collect()),
+        }
+    }
+}
+
+impl CapabilityNegotiator {
+    /// Negotiate the use of the given locally supported capabilities.
+    pub fn new(local: Capabilities) -> Self {
+        CapabilityNegotiator {
+            local,
+            remote: None,
+        }
+    }
+
+    /// The locally supported capabilities.
+    pub fn local(&self) -> Capabilities {
+        self.local
+    }
+
+    /// The capabilities advertised by the remote peer, if its `Capa` message has been received.
+    pub fn remote(&self) -> Option<Capabilities> {
+        self.remote
+    }
+
+    /// The capabilities supported by both peers.
+    pub fn negotiated(&self) -> Capabilities {
+        match self.remote {
+            Some(remote) => self.local.intersection(&remote),
+            None => Capabilities::empty(),
+        }
+    }
+
+    /// The `Capa` message that a peer sends immediately after connecting.
+    pub fn capa(&self) -> Capa<'static> {
+        self.local.capa()
+    }
+
+    /// Adopt the capabilities advertised by the remote peer.
+    ///
+    /// Returns the capabilities supported by both peers.
+    pub fn receive_capa(&mut self, capa: &Capa) -> Capabilities {
+        self.remote = Some(Capabilities::from(capa));
+        self.negotiated()
+    }
+
+    /// Check that an outgoing message only relies upon negotiated capabilities.
+    ///
+    /// ChLs, SXSr and SXUS messages each require their own capability. SXSr and SXUS messages
+    /// with a connection string for Art-Net, BSR E1.31, ETC Net2 or MA-Net also require the
+    /// capability for that protocol. Returns a `CapabilityNotNegotiated` error for messages that
+    /// must not be sent.
+    pub fn check(&self, msg: &SdmxMessage) -> Result<()> {
+        let (capability, connection_string) = match *msg {
+            SdmxMessage::ChLs(_) => (Capa::CHANNEL_LIST, None),
+            SdmxMessage::SXSr(ref msg) => {
+                let connection_string = &msg.message.connection_string;
+                (Capa::EXTERNAL_SOURCE, Some(connection_string))
+            }
+            SdmxMessage::Sxus(ref msg) => {
+                let connection_string = &msg.message.connection_string;
+                (Capa::PER_UNIVERSE_EXTERNAL_SOURCES, Some(connection_string))
+            }
+            _ => return Ok(()),
+        };
+        let source_capability = connection_string.and_then(|s| source_capability(s.as_bytes()));
+        let negotiated = self.negotiated();
+        for capability in Some(capability).into_iter().chain(source_capability) {
+            if !negotiated.contains(capability) {
+                return Err(Error::CapabilityNotNegotiated { capability });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ContentType for Header {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for Capa<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for UNam {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for EnId {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for ChBk<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl<'a> ContentType for ChLs<'a> {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for SXSr {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
+impl ContentType for Sxus {
+    const COOKIE: Cookie = Cookie::from_bytes(*Self::CONTENT_TYPE);
+}
+
 impl WriteToBytes for Header {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.citp_header)?;
         writer.write_u32::<LE>(self.content_type)?;
         Ok(())
@@ -205,7 +443,7 @@ impl<T> WriteToBytes for Message<T>
 where
     T: WriteToBytes,
 {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(self.sdmx_header)?;
         writer.write_bytes(&self.message)?;
         Ok(())
@@ -213,10 +451,13 @@ where
 }
 
 impl<'a> WriteToBytes for Capa<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
-        if self.capabilities.len() > std::u16::MAX as usize {
-            let err_msg = "the number of capabilities exceeds the maximum possible `u16` value";
-            return Err(io::Error::new(io::ErrorKind::InvalidData, err_msg));
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
+        if self.capabilities.len() > u16::MAX as usize {
+            return Err(Error::CountOverflow {
+                field: "capability_count",
+                count: self.capabilities.len(),
+                max: u16::MAX as usize,
+            });
         }
         writer.write_u16::<LE>(self.capabilities.len() as u16)?;
         for &cap in self.capabilities.iter() {
@@ -227,7 +468,7 @@ impl<'a> WriteToBytes for Capa<'a> {
 }
 
 impl WriteToBytes for UNam {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.universe_index)?;
         writer.write_bytes(&self.universe_name)?;
         Ok(())
@@ -235,14 +476,14 @@ impl WriteToBytes for UNam {
 }
 
 impl WriteToBytes for EnId {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(&self.identifier)?;
         Ok(())
     }
 }
 
 impl<'a> WriteToBytes for ChBk<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.blind)?;
         writer.write_u8(self.universe_index)?;
         writer.write_u16::<LE>(self.first_channel)?;
@@ -255,7 +496,7 @@ impl<'a> WriteToBytes for ChBk<'a> {
 }
 
 impl WriteToBytes for ChannelLevel {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.universe_index)?;
         writer.write_u16::<LE>(self.channel)?;
         writer.write_u8(self.channel_level)?;
@@ -264,7 +505,7 @@ impl WriteToBytes for ChannelLevel {
 }
 
 impl<'a> WriteToBytes for ChLs<'a> {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u16::<LE>(self.channel_levels.len() as _)?;
         for ch in self.channel_levels.iter() {
             writer.write_bytes(ch)?;
@@ -274,22 +515,53 @@ impl<'a> WriteToBytes for ChLs<'a> {
 }
 
 impl WriteToBytes for SXSr {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_bytes(&self.connection_string)?;
         Ok(())
     }
 }
 
 impl WriteToBytes for Sxus {
-    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> io::Result<()> {
+    fn write_to_bytes<W: WriteBytesExt>(&self, mut writer: W) -> Result<()> {
         writer.write_u8(self.universe_index)?;
         writer.write_bytes(&self.connection_string)?;
         Ok(())
     }
 }
 
+impl ReadFromBytes for Header {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
+        let citp_header: protocol::Header = reader.read_bytes()?;
+        protocol::check_content_type(Self::COOKIE, citp_header.content_type)?;
+        let content_type = reader.read_u32::<LE>()?;
+        let header = Header {
+            citp_header,
+            content_type,
+        };
+        Ok(header)
+    }
+}
+
+impl<T> ReadFromBytes for Message<T>
+where
+    T: ContentType + ReadFromBytes,
+{
+    fn read_from_bytes<R: ReadBytesExt>(reader: R) -> Result<Self> {
+        let mut reader = protocol::CountingReader::new(reader);
+        let sdmx_header: Header = reader.read_bytes()?;
+        protocol::check_content_type(T::COOKIE, sdmx_header.content_type)?;
+        let message = reader.read_bytes()?;
+        protocol::check_message_size(&sdmx_header.citp_header, reader.count())?;
+        let msg = Message {
+            sdmx_header,
+            message,
+        };
+        Ok(msg)
+    }
+}
+
 impl ReadFromBytes for Capa<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let capability_count: u16 = reader.read_bytes()?;
         let capabilities = protocol::read_new_vec(reader, capability_count as _)?;
         let capabilities = Capa {
@@ -300,7 +572,7 @@ impl ReadFromBytes for Capa<'static> {
 }
 
 impl ReadFromBytes for UNam {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let universe_index = reader.read_u8()?;
         let universe_name = reader.read_bytes()?;
         let unam = UNam {
@@ -312,7 +584,7 @@ impl ReadFromBytes for UNam {
 }
 
 impl ReadFromBytes for EnId {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let identifier = reader.read_bytes()?;
         let enid = EnId { identifier };
         Ok(enid)
@@ -320,12 +592,12 @@ impl ReadFromBytes for EnId {
 }
 
 impl ReadFromBytes for ChBk<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let blind = reader.read_u8()?;
         let universe_index = reader.read_u8()?;
         let first_channel = reader.read_u16::<LE>()?;
         let channel_level_count: u16 = reader.read_u16::<LE>()?;
-        let channel_levels = protocol::read_new_vec(reader, channel_level_count as _)?;
+        let channel_levels = protocol::read_new_bytes(reader, channel_level_count as _)?;
         let channel_levels = Cow::Owned(channel_levels);
         let chbk = ChBk {
             blind,
@@ -338,7 +610,7 @@ impl ReadFromBytes for ChBk<'static> {
 }
 
 impl ReadFromBytes for ChannelLevel {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let universe_index = reader.read_u8()?;
         let channel = reader.read_u16::<LE>()?;
         let channel_level = reader.read_u8()?;
@@ -352,7 +624,7 @@ impl ReadFromBytes for ChannelLevel {
 }
 
 impl ReadFromBytes for ChLs<'static> {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let channel_level_count = reader.read_u16::<LE>()?;
         let channel_levels = protocol::read_new_vec(reader, channel_level_count as _)?;
         let channel_levels = Cow::Owned(channel_levels);
@@ -362,7 +634,7 @@ impl ReadFromBytes for ChLs<'static> {
 }
 
 impl ReadFromBytes for SXSr {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let connection_string = reader.read_bytes()?;
         let sxsr = SXSr { connection_string };
         Ok(sxsr)
@@ -370,7 +642,7 @@ impl ReadFromBytes for SXSr {
 }
 
 impl ReadFromBytes for Sxus {
-    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> io::Result<Self> {
+    fn read_from_bytes<R: ReadBytesExt>(mut reader: R) -> Result<Self> {
         let universe_index = reader.read_u8()?;
         let connection_string = reader.read_bytes()?;
         let sxus = Sxus {
@@ -381,6 +653,43 @@ impl ReadFromBytes for Sxus {
     }
 }
 
+impl<'a, 'b> From<&'b Capa<'a>> for Capabilities {
+    fn from(capa: &'b Capa<'a>) -> Self {
+        let mut capabilities = Capabilities::empty();
+        for &capability in capa.capabilities.iter() {
+            capabilities.insert(capability);
+        }
+        capabilities
+    }
+}
+
+impl From<Capabilities> for Capa<'static> {
+    fn from(capabilities: Capabilities) -> Self {
+        capabilities.capa()
+    }
+}
+
+impl fmt::Debug for Capabilities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl SizeBytes for Header {
+    fn size_bytes(&self) -> usize {
+        self.citp_header.size_bytes() + mem::size_of::<u32>()
+    }
+}
+
+impl<T> SizeBytes for Message<T>
+where
+    T: SizeBytes,
+{
+    fn size_bytes(&self) -> usize {
+        self.sdmx_header.size_bytes() + self.message.size_bytes()
+    }
+}
+
 impl<'a> SizeBytes for Capa<'a> {
     fn size_bytes(&self) -> usize {
         mem::size_of::<u16>() + self.capabilities.len() * mem::size_of::<u16>()
@@ -411,10 +720,20 @@ impl<'a> SizeBytes for ChBk<'a> {
 
 impl<'a> SizeBytes for ChLs<'a> {
     fn size_bytes(&self) -> usize {
-        mem::size_of::<u16>() + self.channel_levels.len() * mem::size_of::<ChannelLevel>()
+        mem::size_of::<u16>() + self.channel_levels.len() * ChannelLevel::SIZE_BYTES
     }
 }
 
+impl SizeBytes for ChannelLevel {
+    fn size_bytes(&self) -> usize {
+        Self::SIZE_BYTES
+    }
+}
+
+impl ConstSizeBytes for ChannelLevel {
+    const SIZE_BYTES: usize = 4;
+}
+
 impl SizeBytes for SXSr {
     fn size_bytes(&self) -> usize {
         self.connection_string.size_bytes()
@@ -426,3 +745,26 @@ impl SizeBytes for Sxus {
         mem::size_of::<u8>() + self.connection_string.size_bytes()
     }
 }
+
+/// The bit representing the given capability within `Capabilities`.
+fn bit(capability: u16) -> Option<u8> {
+    Capa::ALL
+        .iter()
+        .position(|&c| c == capability)
+        .map(|position| 1 << position)
+}
+
+/// The capability required by the protocol of an external source connection string.
+fn source_capability(connection_string: &[u8]) -> Option<u16> {
+    let protocol = connection_string.split(|&b| b == b'/').next()?;
+    let capabilities = [
+        (&b"ArtNet"[..], Capa::ART_NET_EXTERNAL_SOURCES),
+        (&b"BSRE1.31"[..], Capa::BSR_E131_EXTERNAL_SOURCES),
+        (&b"EtcNet2"[..], Capa::ETC_NET2_EXTERNAL_SOURCES),
+        (&b"MANet"[..], Capa::MA_NET_EXTERNAL_SOURCES),
+    ];
+    capabilities
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(protocol))
+        .map(|&(_, capability)| capability)
+}
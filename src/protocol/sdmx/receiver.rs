@@ -0,0 +1,121 @@
+use super::{Buffer, Changes, DmxState};
+use crate::protocol::SdmxMessage;
+use crate::Result;
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+/// Maintains the DMX levels received from a peer and decides whether the live or blind levels of
+/// each universe should currently be displayed.
+///
+/// Following the recommendation of the ChBk docs, a universe switches over to its blind levels as
+/// soon as a blind ChBk block is received for it, and reverts back to its live levels once no
+/// blind block has been received for longer than the blind timeout.
+///
+/// The receiver does not read the clock itself. The current time is instead passed to each method
+/// that depends upon it.
+#[derive(Clone, Debug)]
+pub struct Receiver {
+    blind_timeout: Duration,
+    state: DmxState,
+    last_blind: BTreeMap<u8, Instant>,
+}
+
+impl Receiver {
+    /// The default duration for which blind levels remain active after the last blind block.
+    pub const DEFAULT_BLIND_TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// Create a receiver that reverts a universe to its live levels once `blind_timeout` has
+    /// elapsed without receiving a blind block for it.
+    pub fn new(blind_timeout: Duration) -> Self {
+        Receiver {
+            blind_timeout,
+            state: DmxState::new(),
+            last_blind: BTreeMap::new(),
+        }
+    }
+
+    /// The duration for which blind levels remain active after the last blind block.
+    pub fn blind_timeout(&self) -> Duration {
+        self.blind_timeout
+    }
+
+    /// Apply the levels carried by a ChBk or ChLs message received at `now`.
+    ///
+    /// All other messages are ignored.
+    pub fn update(&mut self, msg: &SdmxMessage, now: Instant) -> Result<Changes> {
+        let changes = self.state.update(msg)?;
+        if let SdmxMessage::ChBk(ref msg) = *msg {
+            if msg.message.blind != 0 {
+                self.last_blind.insert(msg.message.universe_index, now);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Whether the live or the blind levels of the given universe are active at `now`.
+    pub fn active_buffer(&self, universe_index: u8, now: Instant) -> Buffer {
+        match self.last_blind.get(&universe_index) {
+            Some(&last) if now.saturating_duration_since(last) < self.blind_timeout => {
+                Buffer::Blind
+            }
+            _ => Buffer::Live,
+        }
+    }
+
+    /// All `DmxState::CHANNEL_COUNT` active levels of the given universe.
+    ///
+    /// Returns `None` if no levels have been received for the active buffer of the universe.
+    pub fn active_levels(&self, universe_index: u8, now: Instant) -> Option<&[u8]> {
+        let buffer = self.active_buffer(universe_index, now);
+        self.state.levels(buffer, universe_index)
+    }
+
+    /// The active levels of a single DMX512 universe within the wide universe.
+    ///
+    /// See `DmxState::slots`.
+    pub fn active_slots(
+        &self,
+        universe_index: u8,
+        dmx_universe: usize,
+        now: Instant,
+    ) -> Option<&[u8]> {
+        let buffer = self.active_buffer(universe_index, now);
+        self.state.slots(buffer, universe_index, dmx_universe)
+    }
+
+    /// Revert all universes whose blind levels have expired back to their live levels.
+    ///
+    /// The expired blind levels are discarded. Returns the indices of the reverted universes in
+    /// ascending order.
+    pub fn revert_expired(&mut self, now: Instant) -> Vec<u8> {
+        let timeout = self.blind_timeout;
+        let expired: Vec<u8> = self
+            .last_blind
+            .iter()
+            .filter(|&(_, &last)| now.saturating_duration_since(last) >= timeout)
+            .map(|(&universe_index, _)| universe_index)
+            .collect();
+        for &universe_index in &expired {
+            self.last_blind.remove(&universe_index);
+            self.state.remove(Buffer::Blind, universe_index);
+        }
+        expired
+    }
+
+    /// The live and blind levels received so far.
+    pub fn state(&self) -> &DmxState {
+        &self.state
+    }
+
+    /// Forget all received levels.
+    pub fn clear(&mut self) {
+        self.state.clear();
+        self.last_blind.clear();
+    }
+}
+
+impl Default for Receiver {
+    fn default() -> Self {
+        Receiver::new(Self::DEFAULT_BLIND_TIMEOUT)
+    }
+}
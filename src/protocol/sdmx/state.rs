@@ -0,0 +1,211 @@
+use super::{ChBk, ChLs};
+use crate::protocol::SdmxMessage;
+use crate::{Error, Result};
+use std::collections::BTreeMap;
+use std::vec;
+
+/// The DMX levels described by the ChBk and ChLs messages received from a peer.
+///
+/// Each universe index addresses a single - wide - universe of up to 65_536 channels. Live and
+/// blind levels are tracked separately, as ChBk blocks may carry either. ChLs messages always
+/// carry live levels. The levels of a universe are allocated upon the first message that
+/// addresses it and channels that have never been received have a level of `0`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DmxState {
+    live: BTreeMap<u8, Box<[u8]>>,
+    blind: BTreeMap<u8, Box<[u8]>>,
+}
+
+/// The set of levels to which a channel level belongs.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Buffer {
+    /// The levels currently output by the console.
+    Live,
+    /// Levels previewed by the console without being output, e.g. while editing a cue.
+    Blind,
+}
+
+/// A change to the level of a single channel.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ChannelChange {
+    /// The set of levels in which the change occurred.
+    pub buffer: Buffer,
+    /// `0`-based index of the universe.
+    pub universe_index: u8,
+    /// `0`-based index of the channel in the universe.
+    pub channel: u16,
+    /// The level prior to the change.
+    pub old: u8,
+    /// The level following the change.
+    pub new: u8,
+}
+
+/// An iterator yielding the channels changed by a message, in the order they were applied.
+#[derive(Clone, Debug)]
+pub struct Changes {
+    changes: vec::IntoIter<ChannelChange>,
+}
+
+impl DmxState {
+    /// The number of channels within a single wide universe.
+    pub const CHANNEL_COUNT: usize = 1 << 16;
+    /// The number of slots within a single DMX512 universe.
+    pub const SLOT_COUNT: usize = 512;
+
+    /// Create a state in which no universes have been received.
+    pub fn new() -> Self {
+        DmxState {
+            live: BTreeMap::new(),
+            blind: BTreeMap::new(),
+        }
+    }
+
+    /// Apply the levels carried by a ChBk or ChLs message.
+    ///
+    /// All other messages are ignored.
+    pub fn update(&mut self, msg: &SdmxMessage) -> Result<Changes> {
+        match *msg {
+            SdmxMessage::ChBk(ref msg) => self.apply_chbk(&msg.message),
+            SdmxMessage::ChLs(ref msg) => Ok(self.apply_chls(&msg.message)),
+            _ => Ok(Changes::new(vec![])),
+        }
+    }
+
+    /// Apply a block of consecutive levels starting at the block's `first_channel`.
+    ///
+    /// Returns an error reporting the last channel of the block without applying any levels if the
+    /// block extends beyond the last channel of the universe.
+    pub fn apply_chbk(&mut self, chbk: &ChBk) -> Result<Changes> {
+        let first = chbk.first_channel as usize;
+        let end = first + chbk.channel_levels.len();
+        if end > Self::CHANNEL_COUNT {
+            return Err(Error::ChannelOutOfRange { channel: end - 1 });
+        }
+        let buffer = match chbk.blind {
+            0 => Buffer::Live,
+            _ => Buffer::Blind,
+        };
+        let levels = self.levels_mut(buffer, chbk.universe_index);
+        let mut changes = vec![];
+        let targets = levels[first..end]
+            .iter_mut()
+            .zip(chbk.channel_levels.iter());
+        for (channel, (old, &new)) in (first..end).zip(targets) {
+            if *old != new {
+                changes.push(ChannelChange {
+                    buffer,
+                    universe_index: chbk.universe_index,
+                    channel: channel as u16,
+                    old: *old,
+                    new,
+                });
+                *old = new;
+            }
+        }
+        Ok(Changes::new(changes))
+    }
+
+    /// Apply a list of live levels, each addressing its own universe and channel.
+    pub fn apply_chls(&mut self, chls: &ChLs) -> Changes {
+        let mut changes = vec![];
+        for ch in chls.channel_levels.iter() {
+            let levels = self.levels_mut(Buffer::Live, ch.universe_index);
+            let old = &mut levels[ch.channel as usize];
+            if *old != ch.channel_level {
+                changes.push(ChannelChange {
+                    buffer: Buffer::Live,
+                    universe_index: ch.universe_index,
+                    channel: ch.channel,
+                    old: *old,
+                    new: ch.channel_level,
+                });
+                *old = ch.channel_level;
+            }
+        }
+        Changes::new(changes)
+    }
+
+    /// All `CHANNEL_COUNT` levels of the given universe.
+    ///
+    /// Returns `None` if no levels have been received for the universe.
+    pub fn levels(&self, buffer: Buffer, universe_index: u8) -> Option<&[u8]> {
+        self.buffers(buffer)
+            .get(&universe_index)
+            .map(|levels| &levels[..])
+    }
+
+    /// The level of a single channel. `0` if no level has been received for the channel.
+    pub fn level(&self, buffer: Buffer, universe_index: u8, channel: u16) -> u8 {
+        self.levels(buffer, universe_index)
+            .map(|levels| levels[channel as usize])
+            .unwrap_or(0)
+    }
+
+    /// The `SLOT_COUNT` levels of the DMX512 universe at the given `0`-based offset within the
+    /// wide universe, i.e. `dmx_universe` `1` views channels `512..1024`.
+    ///
+    /// Returns `None` if no levels have been received for the universe or if `dmx_universe` lies
+    /// beyond the end of the wide universe.
+    pub fn slots(&self, buffer: Buffer, universe_index: u8, dmx_universe: usize) -> Option<&[u8]> {
+        let start = dmx_universe.checked_mul(Self::SLOT_COUNT)?;
+        let levels = self.levels(buffer, universe_index)?;
+        levels.get(start..start + Self::SLOT_COUNT)
+    }
+
+    /// The indices of all universes for which levels have been received, in ascending order.
+    pub fn universe_indices(&self, buffer: Buffer) -> impl Iterator<Item = u8> + '_ {
+        self.buffers(buffer).keys().cloned()
+    }
+
+    /// Forget the levels of the given universe.
+    pub fn remove(&mut self, buffer: Buffer, universe_index: u8) {
+        self.buffers_mut(buffer).remove(&universe_index);
+    }
+
+    /// Forget the levels of all universes.
+    pub fn clear(&mut self) {
+        self.live.clear();
+        self.blind.clear();
+    }
+
+    fn buffers(&self, buffer: Buffer) -> &BTreeMap<u8, Box<[u8]>> {
+        match buffer {
+            Buffer::Live => &self.live,
+            Buffer::Blind => &self.blind,
+        }
+    }
+
+    fn buffers_mut(&mut self, buffer: Buffer) -> &mut BTreeMap<u8, Box<[u8]>> {
+        match buffer {
+            Buffer::Live => &mut self.live,
+            Buffer::Blind => &mut self.blind,
+        }
+    }
+
+    fn levels_mut(&mut self, buffer: Buffer, universe_index: u8) -> &mut [u8] {
+        self.buffers_mut(buffer)
+            .entry(universe_index)
+            .or_insert_with(|| vec![0; Self::CHANNEL_COUNT].into_boxed_slice())
+    }
+}
+
+impl Changes {
+    fn new(changes: Vec<ChannelChange>) -> Self {
+        Changes {
+            changes: changes.into_iter(),
+        }
+    }
+}
+
+impl Iterator for Changes {
+    type Item = ChannelChange;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.changes.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.changes.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Changes {}
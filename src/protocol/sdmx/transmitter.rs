@@ -0,0 +1,215 @@
+use super::{Capa, Capabilities, ChBk, ChLs, ChannelLevel, DmxState, Message};
+use crate::protocol::{SdmxMessage, SizeBytes};
+use crate::{Error, Result};
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::time::{Duration, Instant};
+
+/// Produces the SDMX messages required to bring a peer up to date with the live levels of each
+/// universe.
+///
+/// The levels of each universe are compared against those that were last transmitted and only
+/// the channels that have changed are sent. Changes are either sent as ChBk blocks spanning runs of
+/// changed channels or as a single ChLs list, whichever requires fewer bytes. ChLs is only
+/// considered once the peer has advertised `Capa::CHANNEL_LIST`.
+///
+/// As SDMX is commonly sent over unreliable transports, all levels of a universe are resent once
+/// the refresh interval has elapsed. Each message carries at most `max_block_size` levels so that
+/// it fits within a single datagram.
+#[derive(Clone, Debug)]
+pub struct Transmitter {
+    refresh_interval: Duration,
+    max_block_size: usize,
+    channel_list: bool,
+    sent: BTreeMap<u8, Sent>,
+}
+
+#[derive(Clone, Debug)]
+struct Sent {
+    levels: Vec<u8>,
+    last_refresh: Instant,
+}
+
+impl Transmitter {
+    /// The default interval at which all levels of a universe are resent.
+    pub const DEFAULT_REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+    /// The default maximum number of levels carried by each message, a single DMX512 universe.
+    pub const DEFAULT_MAX_BLOCK_SIZE: usize = 512;
+
+    /// Create a transmitter that resends all levels of a universe every `refresh_interval`.
+    ///
+    /// Only ChBk messages are produced until the peer advertises support for ChLs.
+    pub fn new(refresh_interval: Duration) -> Self {
+        Transmitter {
+            refresh_interval,
+            max_block_size: Self::DEFAULT_MAX_BLOCK_SIZE,
+            channel_list: false,
+            sent: BTreeMap::new(),
+        }
+    }
+
+    /// Specify the maximum number of levels carried by each ChBk or ChLs message.
+    ///
+    /// **Panics** if `max_block_size` is `0` or greater than `u16::MAX`.
+    pub fn with_max_block_size(mut self, max_block_size: usize) -> Self {
+        assert!(
+            max_block_size > 0 && max_block_size <= u16::MAX as usize,
+            "`max_block_size` must be within 1 and `u16::MAX`"
+        );
+        self.max_block_size = max_block_size;
+        self
+    }
+
+    /// The interval at which all levels of a universe are resent.
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh_interval
+    }
+
+    /// The maximum number of levels carried by each ChBk or ChLs message.
+    pub fn max_block_size(&self) -> usize {
+        self.max_block_size
+    }
+
+    /// Update the capabilities that may be used with the peer.
+    ///
+    /// Typically the capabilities negotiated by a `CapabilityNegotiator`.
+    pub fn set_capabilities(&mut self, capabilities: Capabilities) {
+        self.channel_list = capabilities.contains(Capa::CHANNEL_LIST);
+    }
+
+    /// Whether or not changes may be sent via ChLs messages.
+    pub fn channel_list_enabled(&self) -> bool {
+        self.channel_list
+    }
+
+    /// Produce the messages that update the peer with the given live levels of a universe.
+    ///
+    /// `levels` begins at the first channel of the universe and may contain at most
+    /// `DmxState::CHANNEL_COUNT` levels. Returns no messages if no levels have changed since the
+    /// last transmission and no refresh is due.
+    pub fn transmit(
+        &mut self,
+        universe_index: u8,
+        levels: &[u8],
+        now: Instant,
+    ) -> Result<Vec<SdmxMessage>> {
+        if levels.len() > DmxState::CHANNEL_COUNT {
+            return Err(Error::ChannelOutOfRange {
+                channel: levels.len() - 1,
+            });
+        }
+
+        let refresh_interval = self.refresh_interval;
+        let max_block_size = self.max_block_size;
+        let sent = match self.sent.get_mut(&universe_index) {
+            Some(sent) if now.saturating_duration_since(sent.last_refresh) < refresh_interval => {
+                sent
+            }
+            _ => {
+                let sent = Sent {
+                    levels: levels.to_vec(),
+                    last_refresh: now,
+                };
+                self.sent.insert(universe_index, sent);
+                let runs = Some(0..levels.len());
+                let chbks = chbk_messages(universe_index, levels, runs, max_block_size);
+                return Ok(chbks.into_iter().map(SdmxMessage::ChBk).collect());
+            }
+        };
+
+        let changed: Vec<usize> = (0..levels.len())
+            .filter(|&i| sent.levels.get(i) != Some(&levels[i]))
+            .collect();
+        sent.levels.clear();
+        sent.levels.extend_from_slice(levels);
+        if changed.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let runs = changed_runs(&changed);
+        let chbks = chbk_messages(universe_index, levels, runs, max_block_size);
+        if self.channel_list && changed.len() <= max_block_size {
+            let channel_levels = changed
+                .iter()
+                .map(|&i| ChannelLevel {
+                    universe_index,
+                    channel: i as u16,
+                    channel_level: levels[i],
+                })
+                .collect();
+            let chls = Message::new(ChLs {
+                channel_levels: Cow::Owned(channel_levels),
+            });
+            let chbks_size: usize = chbks.iter().map(SizeBytes::size_bytes).sum();
+            if chls.size_bytes() < chbks_size {
+                return Ok(vec![SdmxMessage::ChLs(chls)]);
+            }
+        }
+        Ok(chbks.into_iter().map(SdmxMessage::ChBk).collect())
+    }
+
+    /// Forget the levels last transmitted for the given universe, so that all of its levels are
+    /// sent upon the next transmission.
+    pub fn remove(&mut self, universe_index: u8) {
+        self.sent.remove(&universe_index);
+    }
+
+    /// Forget the levels last transmitted for all universes, e.g. after reconnecting to the peer.
+    pub fn clear(&mut self) {
+        self.sent.clear();
+    }
+}
+
+impl Default for Transmitter {
+    fn default() -> Self {
+        Transmitter::new(Self::DEFAULT_REFRESH_INTERVAL)
+    }
+}
+
+/// Group the sorted indices of changed channels into runs.
+///
+/// Neighbouring runs are merged whenever resending the unchanged channels between them is
+/// cheaper than the overhead of an additional ChBk message.
+fn changed_runs(changed: &[usize]) -> Vec<Range<usize>> {
+    let empty = Message::new(ChBk {
+        blind: 0,
+        universe_index: 0,
+        first_channel: 0,
+        channel_levels: Cow::Borrowed(&[]),
+    });
+    let overhead = empty.size_bytes();
+    let mut runs: Vec<Range<usize>> = vec![];
+    for &i in changed {
+        match runs.last_mut() {
+            Some(run) if i - run.end <= overhead => run.end = i + 1,
+            _ => runs.push(i..i + 1),
+        }
+    }
+    runs
+}
+
+/// A ChBk message for each run of levels, splitting runs that exceed the maximum block size.
+fn chbk_messages(
+    universe_index: u8,
+    levels: &[u8],
+    runs: impl IntoIterator<Item = Range<usize>>,
+    max_block_size: usize,
+) -> Vec<Message<ChBk<'static>>> {
+    let mut chbks = vec![];
+    for run in runs {
+        for (i, block) in levels[run.start..run.end]
+            .chunks(max_block_size)
+            .enumerate()
+        {
+            let first_channel = run.start + i * max_block_size;
+            chbks.push(Message::new(ChBk {
+                blind: 0,
+                universe_index,
+                first_channel: first_channel as u16,
+                channel_levels: Cow::Owned(block.to_vec()),
+            }));
+        }
+    }
+    chbks
+}
@@ -0,0 +1,104 @@
+use citp::net::announce::{PeerAnnouncer, Targets};
+use citp::net::discovery::{Discovery, PeerEvent};
+use citp::protocol::{pinf, Packet, PinfMessage};
+use std::ffi::CString;
+use std::net::{Ipv4Addr, UdpSocket};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A port that is free on the loopback interface.
+fn free_port() -> u16 {
+    let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+    socket.local_addr().unwrap().port()
+}
+
+fn ploc(state: &str) -> pinf::PLoc {
+    pinf::PLoc {
+        listening_tcp_port: 6436,
+        kind: pinf::PeerKind::MediaServer,
+        name: CString::new("server").unwrap(),
+        state: CString::new(state).unwrap(),
+    }
+}
+
+/// Poll the discovery until it produces events, for at most five seconds.
+fn events(discovery: &mut Discovery, now: Instant) -> Vec<PeerEvent> {
+    for _ in 0..500 {
+        let events = discovery.poll(now).unwrap();
+        if !events.is_empty() {
+            return events;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("no announcement was received");
+}
+
+#[test]
+fn announcements_are_received_periodically() {
+    let t0 = Instant::now();
+    let interval = Duration::from_secs(1);
+    let port = free_port();
+    let mut discovery = Discovery::bind(Ipv4Addr::LOCALHOST, port).unwrap();
+    let mut announcer = PeerAnnouncer::bind(Ipv4Addr::LOCALHOST, ploc("Idle"))
+        .unwrap()
+        .with_port(port)
+        .with_interval(interval)
+        .with_targets(Targets::Current);
+
+    assert!(announcer.poll(t0).unwrap());
+    let ip = match &events(&mut discovery, t0)[..] {
+        [PeerEvent::Added(peer)] => {
+            assert_eq!(peer.name, "server");
+            assert_eq!(peer.state, "Idle");
+            peer.ip
+        }
+        events => panic!("unexpected events: {:?}", events),
+    };
+
+    // Nothing is sent until the interval has elapsed. Each announcement refreshes the peer.
+    let just_before = t0 + interval - Duration::from_nanos(1);
+    assert!(!announcer.poll(just_before).unwrap());
+    let t1 = t0 + interval;
+    assert!(announcer.poll(t1).unwrap());
+    for _ in 0..500 {
+        discovery.poll(t1).unwrap();
+        if discovery.peers().get(&ip).unwrap().last_seen == t1 {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert_eq!(discovery.peers().get(&ip).unwrap().last_seen, t1);
+
+    // A change of state is announced immediately.
+    announcer.set_state(CString::new("Running").unwrap());
+    assert!(announcer.poll(t1).unwrap());
+    match &events(&mut discovery, t1)[..] {
+        [PeerEvent::Updated(peer), ..] => assert_eq!(peer.state, "Running"),
+        events => panic!("unexpected events: {:?}", events),
+    }
+}
+
+#[test]
+fn pnam_is_broadcast_with_each_announcement() {
+    let receiver = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+    receiver
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let pnam_port = receiver.local_addr().unwrap().port();
+    let mut announcer = PeerAnnouncer::bind(Ipv4Addr::LOCALHOST, ploc("Idle"))
+        .unwrap()
+        .with_port(free_port())
+        .with_pnam_broadcast(true)
+        .unwrap()
+        .with_pnam_port(pnam_port);
+
+    announcer.announce(Instant::now()).unwrap();
+    let mut buffer = [0; 512];
+    let len = receiver.recv(&mut buffer).unwrap();
+    match Packet::decode(&buffer[..len]).unwrap() {
+        Packet::Pinf(PinfMessage::PNam(msg)) => {
+            assert_eq!(msg.message.name, CString::new("server").unwrap());
+        }
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+}
@@ -0,0 +1,210 @@
+use citp::protocol::msex::client::{MediaServerEvent, MediaServerState};
+use citp::protocol::msex::{
+    self, ELIn, ELUp, LSta, LayerStatus, LibraryId, LibraryInformation, LibraryType, MEIn,
+    MediaInformation, SizeBytesVersioned, Version, WriteToBytesVersioned,
+};
+use citp::protocol::{ContentType, MsexMessage, Packet, WriteBytes};
+use std::borrow::Cow;
+
+/// Encode the message as an MSEX 1.2 packet and decode it again, as if received from a server.
+fn received<T>(message: T) -> MsexMessage
+where
+    T: ContentType + SizeBytesVersioned + WriteToBytesVersioned,
+{
+    let mut bytes = vec![];
+    bytes
+        .write_bytes(msex::Message::new(Version::V1_2, message))
+        .unwrap();
+    match Packet::decode(&bytes).unwrap() {
+        Packet::Msex(msg) => msg,
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+}
+
+fn layer(layer_number: u8, media_number: u8, flags: u32) -> LayerStatus {
+    LayerStatus {
+        layer_number,
+        physical_output: 0,
+        media_library_type: LibraryType::Media,
+        media_library_id: LibraryId::from_number(1),
+        media_number,
+        media_name: "clip".parse().unwrap(),
+        media_position: 0,
+        media_length: 100,
+        media_fps: 30,
+        layer_status_flags: flags,
+    }
+}
+
+fn lsta(layers: Vec<LayerStatus>) -> LSta<'static> {
+    LSta {
+        layer_count: layers.len() as u8,
+        layers: Cow::Owned(layers),
+    }
+}
+
+fn library(id: LibraryId, name: &str) -> LibraryInformation {
+    LibraryInformation {
+        id,
+        serial_number: 0,
+        dmx_range_min: 0,
+        dmx_range_max: 0,
+        name: name.parse().unwrap(),
+        library_count: 0,
+        element_count: 1,
+    }
+}
+
+fn elin(libraries: Vec<LibraryInformation>) -> ELIn<'static> {
+    ELIn {
+        library_type: LibraryType::Media,
+        library_count: libraries.len() as u16,
+        libraries: Cow::Owned(libraries),
+    }
+}
+
+fn child(parent: u8, number: u8) -> LibraryId {
+    LibraryId {
+        level: 2,
+        level_1: parent,
+        level_2: number,
+        level_3: 0,
+    }
+}
+
+#[test]
+fn layer_status_changes_produce_events() {
+    let mut state = MediaServerState::new();
+    let events = state.update(&received(lsta(vec![
+        layer(0, 1, LayerStatus::MEDIA_PLAYING),
+        layer(1, 1, 0),
+    ])));
+    assert_eq!(
+        events,
+        vec![MediaServerEvent::LayerStartedPlaying { layer_number: 0 }]
+    );
+
+    let events = state.update(&received(lsta(vec![
+        layer(0, 1, 0),
+        layer(1, 2, LayerStatus::MEDIA_PLAYING),
+    ])));
+    assert_eq!(
+        events,
+        vec![
+            MediaServerEvent::LayerStoppedPlaying { layer_number: 0 },
+            MediaServerEvent::LayerMediaChanged { layer_number: 1 },
+            MediaServerEvent::LayerStartedPlaying { layer_number: 1 },
+        ]
+    );
+    assert_eq!(
+        state.layer(1).unwrap().media_library_type,
+        LibraryType::Media
+    );
+
+    // Layers that are no longer listed are forgotten.
+    state.update(&received(lsta(vec![layer(1, 2, 0)])));
+    assert!(state.layer(0).is_none());
+    assert_eq!(state.layers().count(), 1);
+}
+
+#[test]
+fn elin_only_adds_and_updates_listed_libraries() {
+    let mut state = MediaServerState::new();
+    let events = state.update(&received(elin(vec![
+        library(LibraryId::from_number(1), "a"),
+        library(LibraryId::from_number(2), "b"),
+    ])));
+    assert_eq!(
+        events,
+        vec![MediaServerEvent::LibrariesReceived {
+            library_type: LibraryType::Media
+        }]
+    );
+    state.update(&received(elin(vec![
+        library(child(1, 1), "a1"),
+        library(child(1, 2), "a2"),
+    ])));
+    assert_eq!(state.libraries(LibraryType::Media).count(), 4);
+
+    // A response to a GELI requesting only library 1 renames it without removing its siblings.
+    state.update(&received(elin(vec![library(
+        LibraryId::from_number(1),
+        "renamed",
+    )])));
+    let root = LibraryId::ROOT;
+    let names: Vec<String> = state
+        .sub_libraries(LibraryType::Media, root)
+        .map(|lib| lib.name.to_string().unwrap())
+        .collect();
+    assert_eq!(names, vec!["renamed", "b"]);
+    let parent = LibraryId::from_number(1);
+    assert_eq!(state.sub_libraries(LibraryType::Media, parent).count(), 2);
+    // Libraries of other types are unaffected.
+    assert_eq!(state.libraries(LibraryType::Effects).count(), 0);
+
+    // Removed libraries are only forgotten once announced via ELUp.
+    let mut affected_libraries = [0; 32];
+    affected_libraries[0] = 1 << 2;
+    let elup = ELUp {
+        library_type: LibraryType::Media,
+        library_id: root,
+        update_flags: ELUp::SUB_LIBRARIES_ADDED_OR_REMOVED,
+        affected_elements: [0; 32],
+        affected_libraries,
+    };
+    state.update(&received(elup));
+    let names: Vec<String> = state
+        .sub_libraries(LibraryType::Media, root)
+        .map(|lib| lib.name.to_string().unwrap())
+        .collect();
+    assert_eq!(names, vec!["renamed"]);
+    assert_eq!(state.sub_libraries(LibraryType::Media, parent).count(), 2);
+}
+
+#[test]
+fn elup_invalidates_elements() {
+    let mut state = MediaServerState::new();
+    let library_id = LibraryId::from_number(1);
+    let media = |number| MediaInformation {
+        number,
+        serial_number: 0,
+        dmx_range_min: number,
+        dmx_range_max: number,
+        media_name: "clip".parse().unwrap(),
+        media_version_timestamp: 0,
+        media_width: 1920,
+        media_height: 1080,
+        media_length: 100,
+        media_fps: 30,
+    };
+    let mein = MEIn {
+        library_id,
+        element_count: 2,
+        elements: Cow::Owned(vec![media(0), media(1)]),
+    };
+    let events = state.update(&received(mein));
+    assert_eq!(
+        events,
+        vec![MediaServerEvent::ElementsReceived {
+            library_type: LibraryType::Media,
+            library_id,
+        }]
+    );
+    assert_eq!(state.elements(LibraryType::Media, library_id).count(), 2);
+
+    let mut affected_elements = [0; 32];
+    affected_elements[0] = 0b10;
+    let elup = ELUp {
+        library_type: LibraryType::Media,
+        library_id,
+        update_flags: ELUp::EXISTING_ELEMENTS_UPDATED,
+        affected_elements,
+        affected_libraries: [0; 32],
+    };
+    state.update(&received(elup));
+    let numbers: Vec<u8> = state
+        .elements(LibraryType::Media, library_id)
+        .map(|element| element.number())
+        .collect();
+    assert_eq!(numbers, vec![0]);
+}
@@ -0,0 +1,78 @@
+use citp::net::discovery::{PeerEvent, PeerTable};
+use citp::protocol::pinf;
+use std::ffi::CString;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+fn ploc(name: &str, state: &str) -> pinf::PLoc {
+    pinf::PLoc {
+        listening_tcp_port: 6436,
+        kind: pinf::PeerKind::MediaServer,
+        name: CString::new(name).unwrap(),
+        state: CString::new(state).unwrap(),
+    }
+}
+
+#[test]
+fn peers_are_added_updated_and_expired() {
+    let t0 = Instant::now();
+    let secs = Duration::from_secs;
+    let addr: SocketAddr = "192.168.0.2:4809".parse().unwrap();
+    let mut table = PeerTable::new(secs(10));
+
+    match table.update(addr, &ploc("server", "Idle"), t0) {
+        Some(PeerEvent::Added(peer)) => {
+            assert_eq!(peer.name, "server");
+            assert_eq!(peer.tcp_addr(), Some("192.168.0.2:6436".parse().unwrap()));
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert_eq!(
+        table.update(addr, &ploc("server", "Idle"), t0 + secs(5)),
+        None
+    );
+    match table.update(addr, &ploc("server", "Running"), t0 + secs(6)) {
+        Some(PeerEvent::Updated(peer)) => assert_eq!(peer.state, "Running"),
+        event => panic!("unexpected event: {:?}", event),
+    }
+
+    // The timeout is measured from the most recent announcement.
+    assert!(table.remove_expired(t0 + secs(15)).is_empty());
+    let removed = table.remove_expired(t0 + secs(16));
+    assert_eq!(removed.len(), 1);
+    assert!(matches!(removed[0], PeerEvent::Removed(ref peer) if peer.ip == addr.ip()));
+    assert!(table.is_empty());
+}
+
+#[test]
+fn peers_are_identified_by_ip_address() {
+    let t0 = Instant::now();
+    let addr: SocketAddr = "192.168.0.2:4809".parse().unwrap();
+    let mut table = PeerTable::default();
+    assert!(table.update(addr, &ploc("server", "Idle"), t0).is_some());
+
+    // A restarted peer announcing from a new source port remains the same peer.
+    let restarted: SocketAddr = "192.168.0.2:5000".parse().unwrap();
+    assert_eq!(table.update(restarted, &ploc("server", "Idle"), t0), None);
+    assert_eq!(table.len(), 1);
+
+    // A change of listening port is an update to the peer rather than a new peer.
+    let mut moved = ploc("server", "Idle");
+    moved.listening_tcp_port = 6437;
+    match table.update(restarted, &moved, t0) {
+        Some(PeerEvent::Updated(peer)) => {
+            assert_eq!(peer.tcp_addr(), Some("192.168.0.2:6437".parse().unwrap()));
+        }
+        event => panic!("unexpected event: {:?}", event),
+    }
+    assert_eq!(table.len(), 1);
+    let peer = table.get(&addr.ip()).unwrap();
+    assert_eq!(peer.listening_tcp_port, 6437);
+
+    let other: SocketAddr = "192.168.0.3:4809".parse().unwrap();
+    assert!(matches!(
+        table.update(other, &ploc("console", "Idle"), t0),
+        Some(PeerEvent::Added(_))
+    ));
+    assert_eq!(table.len(), 2);
+}
@@ -0,0 +1,156 @@
+use citp::net::fragment::{Fragmenter, Reassembler};
+use citp::protocol::{sdmx, Packet, SdmxMessage};
+use citp::Error;
+use std::ffi::CString;
+use std::time::{Duration, Instant};
+
+fn unam_bytes(universe_name: &str) -> Vec<u8> {
+    let unam = sdmx::UNam {
+        universe_index: 1,
+        universe_name: CString::new(universe_name).unwrap(),
+    };
+    Packet::from(SdmxMessage::UNam(sdmx::Message::new(unam)))
+        .encode()
+        .unwrap()
+}
+
+/// The base header and eight bytes of payload per part.
+fn fragmenter() -> Fragmenter {
+    Fragmenter::new(28)
+}
+
+#[test]
+fn parts_fit_within_the_mtu() {
+    let message = unam_bytes("a long universe name");
+    let parts = fragmenter().fragment(&message).unwrap();
+    let payload_len = message.len() - 20;
+    assert_eq!(parts.len(), payload_len.div_ceil(8));
+    for (i, part) in parts.iter().enumerate() {
+        assert!(part.len() <= 28);
+        assert_eq!(
+            u32::from_le_bytes(part[8..12].try_into().unwrap()) as usize,
+            part.len()
+        );
+        assert_eq!(
+            u16::from_le_bytes(part[12..14].try_into().unwrap()) as usize,
+            parts.len()
+        );
+        assert_eq!(
+            u16::from_le_bytes(part[14..16].try_into().unwrap()) as usize,
+            i
+        );
+    }
+
+    // Messages that already fit are left unchanged.
+    let short = unam_bytes("");
+    assert_eq!(Fragmenter::default().fragment(&short).unwrap(), vec![short]);
+}
+
+#[test]
+fn out_of_order_parts_are_reassembled() {
+    let now = Instant::now();
+    let message = unam_bytes("a long universe name");
+    let parts = fragmenter().fragment(&message).unwrap();
+    let mut reassembler = Reassembler::default();
+    for part in parts.iter().skip(1).rev() {
+        assert_eq!(reassembler.push(0, part, now).unwrap(), None);
+    }
+    assert_eq!(reassembler.len(), 1);
+    let reassembled = reassembler.push(0, &parts[0], now).unwrap().unwrap();
+    assert_eq!(reassembled, message);
+    assert!(reassembler.is_empty());
+    assert!(Packet::decode(&reassembled).is_ok());
+}
+
+#[test]
+fn duplicate_parts_are_ignored() {
+    let now = Instant::now();
+    let message = unam_bytes("a long universe name");
+    let parts = fragmenter().fragment(&message).unwrap();
+    let mut reassembler = Reassembler::default();
+    let (last, rest) = parts.split_last().unwrap();
+    for part in rest {
+        assert_eq!(reassembler.push(0, part, now).unwrap(), None);
+        assert_eq!(reassembler.push(0, part, now).unwrap(), None);
+    }
+    assert_eq!(reassembler.push(0, last, now).unwrap(), Some(message));
+}
+
+#[test]
+fn parts_of_a_new_message_restart_reassembly() {
+    let now = Instant::now();
+    let old = fragmenter()
+        .fragment(&unam_bytes("universe name old"))
+        .unwrap();
+    let new_message = unam_bytes("universe name new");
+    let new = fragmenter().fragment(&new_message).unwrap();
+    assert_eq!(old.len(), new.len());
+
+    // The old message is never completed. Its parts must not be merged into the new message,
+    // which is detected once a part differing from the old one arrives.
+    let mut reassembler = Reassembler::default();
+    for part in &old[1..] {
+        assert_eq!(reassembler.push(0, part, now).unwrap(), None);
+    }
+    assert_ne!(old.last(), new.last());
+    for part in new[1..].iter().rev() {
+        assert_eq!(reassembler.push(0, part, now).unwrap(), None);
+    }
+    assert_eq!(reassembler.len(), 1);
+    assert_eq!(
+        reassembler.push(0, &new[0], now).unwrap(),
+        Some(new_message)
+    );
+}
+
+#[test]
+fn senders_are_reassembled_separately() {
+    let now = Instant::now();
+    let message = unam_bytes("a long universe name");
+    let parts = fragmenter().fragment(&message).unwrap();
+    let mut reassembler = Reassembler::default();
+    for part in &parts[1..] {
+        assert_eq!(reassembler.push(1, part, now).unwrap(), None);
+    }
+    assert_eq!(reassembler.push(2, &parts[0], now).unwrap(), None);
+    assert_eq!(reassembler.len(), 2);
+    assert_eq!(reassembler.push(1, &parts[0], now).unwrap(), Some(message));
+}
+
+#[test]
+fn incomplete_messages_time_out() {
+    let t0 = Instant::now();
+    let timeout = Duration::from_secs(1);
+    let message = unam_bytes("a long universe name");
+    let parts = fragmenter().fragment(&message).unwrap();
+    let mut reassembler = Reassembler::new(timeout);
+    assert_eq!(reassembler.push(0, &parts[0], t0).unwrap(), None);
+    let just_before = t0 + timeout - Duration::from_nanos(1);
+    assert_eq!(reassembler.remove_expired(just_before), 0);
+    assert_eq!(reassembler.remove_expired(t0 + timeout), 1);
+    assert!(reassembler.is_empty());
+
+    // Parts received after the timeout begin the message anew.
+    let later = t0 + timeout;
+    for part in &parts[1..] {
+        assert_eq!(reassembler.push(0, part, later).unwrap(), None);
+    }
+    assert_eq!(reassembler.len(), 1);
+}
+
+#[test]
+fn message_part_must_be_below_the_part_count() {
+    let mut part = fragmenter()
+        .fragment(&unam_bytes("a long universe name"))
+        .unwrap()
+        .remove(0);
+    part[12..14].copy_from_slice(&2u16.to_le_bytes());
+    part[14..16].copy_from_slice(&2u16.to_le_bytes());
+    match Reassembler::default().push(0, &part, Instant::now()) {
+        Err(Error::InvalidMessagePart {
+            message_part: 2,
+            message_part_count: 2,
+        }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
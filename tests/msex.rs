@@ -0,0 +1,706 @@
+use citp::protocol::msex::{
+    self, bgr_to_rgb, CInf, EEIn, ELIn, ELTh, ELUp, EThn, EffectInformation, GenericInformation,
+    LSta, LayerStatus, LibraryId, LibraryInformation, LibraryType, Nack, RqSt, SInf,
+    SizeBytesVersioned, SourceInformation, StFr, StreamFormat, ThumbnailFormat, Uuid, VSrc,
+    Version, VersionNegotiator, WriteToBytesVersioned, GELI, GELT, GLEI,
+};
+use citp::protocol::{ContentType, MsexMessage, Packet, WriteBytes};
+use citp::Error;
+use std::borrow::Cow;
+use std::ffi::CString;
+
+/// The size of the base and MSEX headers.
+const HEADERS_SIZE: usize = 26;
+
+/// Encode the message using the layout of the given MSEX version.
+fn encode<T>(version: Version, message: T) -> Vec<u8>
+where
+    T: ContentType + SizeBytesVersioned + WriteToBytesVersioned,
+{
+    let msg = msex::Message::new(version, message);
+    let message_size = msg.msex_header.citp_header.message_size as usize;
+    let mut bytes = vec![];
+    bytes.write_bytes(msg).unwrap();
+    assert_eq!(bytes.len(), message_size);
+    bytes
+}
+
+/// Decode an encoded message, checking that it carries the given MSEX version.
+fn decode(version: Version, bytes: &[u8]) -> MsexMessage {
+    match Packet::decode(bytes).unwrap() {
+        Packet::Msex(msg) => {
+            assert_eq!(msg.msex_header().version(), version);
+            msg
+        }
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+}
+
+fn sinf() -> SInf<'static> {
+    SInf {
+        uuid: Uuid(*b"00000000-0000-0000-0000-000000000001"),
+        product_name: "ab".parse().unwrap(),
+        product_version_major: 1,
+        product_version_minor: 2,
+        product_version_bugfix: 3,
+        supported_msex_versions_count: 2,
+        supported_msex_versions: Cow::Owned(vec![[1, 0], [1, 2]]),
+        supported_library_types: 0b11,
+        thumbnail_formats_count: 1,
+        thumbnail_formats: Cow::Owned(vec![u32::from_le_bytes(*b"RGB8")]),
+        stream_formats_count: 1,
+        stream_formats: Cow::Owned(vec![u32::from_le_bytes(*b"JPEG")]),
+        layer_count: 1,
+        layer_dmx_sources: Cow::Owned(vec![CString::new("ArtNet/0/0/1").unwrap()]),
+    }
+}
+
+#[test]
+fn sinf_round_trips_with_the_1_2_layout() {
+    let bytes = encode(Version::V1_2, sinf());
+    // UUID, name, version triple, versions list, library types, format lists and layers.
+    assert_eq!(bytes.len(), HEADERS_SIZE + 36 + 6 + 3 + 5 + 2 + 5 + 5 + 14);
+    match decode(Version::V1_2, &bytes) {
+        MsexMessage::SInf(msg) => assert_eq!(msg.message, sinf()),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+#[test]
+fn sinf_round_trips_with_the_1_0_and_1_1_layouts() {
+    // Only the product name, major and minor versions and layers precede MSEX 1.2.
+    let expected = SInf {
+        uuid: Uuid::default(),
+        product_version_bugfix: 0,
+        supported_msex_versions_count: 0,
+        supported_msex_versions: Cow::Owned(vec![]),
+        supported_library_types: 0,
+        thumbnail_formats_count: 0,
+        thumbnail_formats: Cow::Owned(vec![]),
+        stream_formats_count: 0,
+        stream_formats: Cow::Owned(vec![]),
+        ..sinf()
+    };
+    for version in [Version::V1_0, Version::V1_1] {
+        assert_eq!(sinf().size_bytes_versioned(version), 6 + 2 + 14);
+        let bytes = encode(version, sinf());
+        assert_eq!(bytes.len(), HEADERS_SIZE + 6 + 2 + 14);
+        match decode(version, &bytes) {
+            MsexMessage::SInf(msg) => assert_eq!(msg.message, expected),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+fn nack_header(version: Version) -> msex::Header {
+    let nack = Nack {
+        received_content_type: 0,
+    };
+    msex::Message::new(version, nack).msex_header
+}
+
+#[test]
+fn versions_after_1_2_are_unsupported() {
+    let v1_3 = Version { major: 1, minor: 3 };
+    assert!(Version::V1_0.is_supported());
+    assert!(Version::V1_2.is_supported());
+    assert!(!v1_3.is_supported());
+
+    // A message of a later version cannot be read, as its layout may have been extended.
+    let mut bytes = encode(Version::V1_2, sinf());
+    bytes[21] = 3;
+    match Packet::decode(&bytes) {
+        Err(Error::UnsupportedVersion { major: 1, minor: 3 }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn negotiator_selects_the_highest_common_version_of_at_least_1_2() {
+    let mut negotiator = VersionNegotiator::new([Version::V1_0, Version::V1_1, Version::V1_2]);
+    assert_eq!(negotiator.version(), None);
+    assert_eq!(negotiator.outgoing_version(), Version::V1_2);
+
+    // Clients supporting only versions prior to 1.2 do not send a CInf.
+    let cinf = CInf::new(&[Version::V1_0, Version::V1_1]);
+    match negotiator.receive_cinf(&cinf) {
+        Err(Error::NoCommonVersion) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(negotiator.version(), None);
+
+    let v1_3 = Version { major: 1, minor: 3 };
+    let cinf = CInf::new(&[Version::V1_1, Version::V1_2, v1_3]);
+    assert_eq!(negotiator.receive_cinf(&cinf).unwrap(), Version::V1_2);
+    assert_eq!(negotiator.version(), Some(Version::V1_2));
+    assert_eq!(negotiator.outgoing_version(), Version::V1_2);
+
+    negotiator.reset();
+    assert_eq!(negotiator.version(), None);
+}
+
+#[test]
+fn negotiator_selects_a_legacy_version_for_clients_without_cinf() {
+    let mut negotiator = VersionNegotiator::new([Version::V1_0, Version::V1_1, Version::V1_2]);
+    assert_eq!(negotiator.select_legacy_version(), Some(Version::V1_1));
+    assert_eq!(negotiator.version(), Some(Version::V1_1));
+    assert_eq!(negotiator.outgoing_version(), Version::V1_1);
+
+    let mut negotiator = VersionNegotiator::new([Version::V1_2]);
+    assert_eq!(negotiator.select_legacy_version(), None);
+    assert_eq!(negotiator.version(), None);
+}
+
+#[test]
+fn negotiator_adopts_the_version_of_the_sinf() {
+    let mut negotiator = VersionNegotiator::new([Version::V1_0, Version::V1_1]);
+    assert_eq!(negotiator.outgoing_version(), Version::V1_1);
+    let sinf_1_0 = msex::Message::new(Version::V1_0, sinf());
+    assert_eq!(negotiator.receive_sinf(&sinf_1_0).unwrap(), Version::V1_0);
+    assert_eq!(negotiator.outgoing_version(), Version::V1_0);
+    let msg = negotiator.message(Nack {
+        received_content_type: 0,
+    });
+    assert_eq!(msg.msex_header.version(), Version::V1_0);
+
+    let sinf_1_2 = msex::Message::new(Version::V1_2, sinf());
+    match negotiator.receive_sinf(&sinf_1_2) {
+        Err(Error::NoCommonVersion) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(negotiator.version(), Some(Version::V1_0));
+}
+
+#[test]
+fn negotiator_checks_received_versions() {
+    let mut negotiator = VersionNegotiator::new([Version::V1_1, Version::V1_2]);
+    // Prior to negotiation any supported version is accepted.
+    assert!(negotiator.check(&nack_header(Version::V1_1)).is_ok());
+    assert!(negotiator.check(&nack_header(Version::V1_0)).is_err());
+
+    negotiator
+        .receive_cinf(&CInf::new(&[Version::V1_2]))
+        .unwrap();
+    assert!(negotiator.check(&nack_header(Version::V1_2)).is_ok());
+    match negotiator.check(&nack_header(Version::V1_1)) {
+        Err(Error::VersionMismatch {
+            expected_major: 1,
+            expected_minor: 2,
+            major: 1,
+            minor: 1,
+        }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+
+    // CInf and SInf are used to establish the version and so are exempt.
+    let cinf = msex::Message::new(Version::V1_0, CInf::new(&[Version::V1_2]));
+    assert!(negotiator.check(&cinf.msex_header).is_ok());
+    let sinf = msex::Message::new(Version::V1_0, sinf());
+    assert!(negotiator.check(&sinf.msex_header).is_ok());
+}
+
+fn layer_status(media_library_type: LibraryType, media_library_id: LibraryId) -> LayerStatus {
+    LayerStatus {
+        layer_number: 1,
+        physical_output: 2,
+        media_library_type,
+        media_library_id,
+        media_number: 3,
+        media_name: "ab".parse().unwrap(),
+        media_position: 4,
+        media_length: 5,
+        media_fps: 30,
+        layer_status_flags: LayerStatus::MEDIA_PLAYING,
+    }
+}
+
+fn lsta(layers: Vec<LayerStatus>) -> LSta<'static> {
+    LSta {
+        layer_count: layers.len() as u8,
+        layers: Cow::Owned(layers),
+    }
+}
+
+#[test]
+fn lsta_round_trips_with_the_1_2_layout() {
+    let id = LibraryId {
+        level: 2,
+        level_1: 1,
+        level_2: 2,
+        level_3: 0,
+    };
+    let layers = vec![layer_status(LibraryType::Effects, id)];
+    let bytes = encode(Version::V1_2, lsta(layers.clone()));
+    // The layer count followed by the library type and four byte library identifier.
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 27);
+    match decode(Version::V1_2, &bytes) {
+        MsexMessage::LSta(msg) => assert_eq!(msg.message.layers[..], layers[..]),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+#[test]
+fn lsta_round_trips_with_the_1_0_and_1_1_layouts() {
+    // Only a `u8` media library number is carried, implying a media library.
+    let layers = vec![
+        layer_status(LibraryType::Media, LibraryId::from_number(7)),
+        layer_status(LibraryType::Media, LibraryId::from_number(8)),
+    ];
+    for version in [Version::V1_0, Version::V1_1] {
+        let bytes = encode(version, lsta(layers.clone()));
+        assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 2 * 23);
+        // The library number directly follows the layer number and physical output.
+        assert_eq!(bytes[HEADERS_SIZE + 1 + 2], 7);
+        match decode(version, &bytes) {
+            MsexMessage::LSta(msg) => {
+                assert_eq!(msg.message.layer_count, 2);
+                assert_eq!(msg.message.layers[..], layers[..]);
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+fn geli(library_type: LibraryType, library_parent_id: LibraryId, numbers: &[u8]) -> GELI<'static> {
+    GELI {
+        library_type,
+        library_parent_id,
+        library_count: numbers.len() as u16,
+        library_numbers: Cow::Owned(numbers.to_vec()),
+    }
+}
+
+#[test]
+fn geli_round_trips_with_each_layout() {
+    let parent = LibraryId::from_number(1);
+    // The parent library identifier is introduced in 1.1 and the count widened in 1.2.
+    let layouts = [
+        (Version::V1_0, LibraryId::ROOT, 1 + 1 + 2),
+        (Version::V1_1, parent, 1 + 4 + 1 + 2),
+        (Version::V1_2, parent, 1 + 4 + 2 + 2),
+    ];
+    for (version, library_parent_id, size) in layouts {
+        let msg = geli(LibraryType::Effects, library_parent_id, &[1, 2]);
+        let bytes = encode(version, msg.clone());
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        match decode(version, &bytes) {
+            MsexMessage::GELI(decoded) => assert_eq!(decoded.message, msg),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+
+    // Counts beyond a `u8` cannot be written prior to 1.2.
+    let numbers: Vec<u8> = (0..=255).chain(Some(0)).collect();
+    let msg = msex::Message::new(Version::V1_1, geli(LibraryType::Media, parent, &numbers));
+    match vec![].write_bytes(msg) {
+        Err(Error::CountOverflow {
+            field: "library_count",
+            count: 257,
+            max: 255,
+        }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn unknown_library_types_are_preserved() {
+    assert_eq!(LibraryType::from(42), LibraryType::Other(42));
+    assert_eq!(u8::from(LibraryType::Other(42)), 42);
+    assert_eq!(LibraryType::from(9), LibraryType::Meshes3D);
+    assert_eq!(LibraryType::Meshes3D.bit(), 1 << 8);
+    assert_eq!(LibraryType::Other(42).bit(), 0);
+
+    let msg = geli(LibraryType::Other(42), LibraryId::ROOT, &[]);
+    match decode(Version::V1_2, &encode(Version::V1_2, msg.clone())) {
+        MsexMessage::GELI(decoded) => assert_eq!(decoded.message, msg),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+fn library_information(id: LibraryId) -> LibraryInformation {
+    LibraryInformation {
+        id,
+        serial_number: 7,
+        dmx_range_min: 1,
+        dmx_range_max: 2,
+        name: "ab".parse().unwrap(),
+        library_count: 3,
+        element_count: 4,
+    }
+}
+
+fn elin(libraries: Vec<LibraryInformation>) -> ELIn<'static> {
+    ELIn {
+        library_type: LibraryType::Media,
+        library_count: libraries.len() as u16,
+        libraries: Cow::Owned(libraries),
+    }
+}
+
+#[test]
+fn elin_round_trips_with_each_layout() {
+    let nested = LibraryId {
+        level: 2,
+        level_1: 1,
+        level_2: 5,
+        level_3: 0,
+    };
+    let msg = elin(vec![library_information(nested)]);
+    let bytes = encode(Version::V1_2, msg.clone());
+    // Identifier, serial number, DMX range, name and `u16` library and element counts.
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 2 + 4 + 4 + 2 + 6 + 2 + 2);
+    match decode(Version::V1_2, &bytes) {
+        MsexMessage::ELIn(decoded) => assert_eq!(decoded.message, msg),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    // 1.1 lacks the serial number and writes the counts as a `u8`.
+    let bytes = encode(Version::V1_1, msg.clone());
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 1 + 4 + 2 + 6 + 1 + 1);
+    let expected = elin(vec![LibraryInformation {
+        serial_number: 0,
+        ..library_information(nested)
+    }]);
+    match decode(Version::V1_1, &bytes) {
+        MsexMessage::ELIn(decoded) => assert_eq!(decoded.message, expected),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    // 1.0 carries only the library number and lacks the sub-library count.
+    let msg = elin(vec![library_information(LibraryId::from_number(5))]);
+    let bytes = encode(Version::V1_0, msg);
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 1 + 1 + 2 + 6 + 1);
+    let expected = elin(vec![LibraryInformation {
+        serial_number: 0,
+        library_count: 0,
+        ..library_information(LibraryId::from_number(5))
+    }]);
+    match decode(Version::V1_0, &bytes) {
+        MsexMessage::ELIn(decoded) => assert_eq!(decoded.message, expected),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+#[test]
+fn elup_round_trips_with_each_layout() {
+    let mut affected_elements = [0; 32];
+    affected_elements[0] = 0b101;
+    let mut affected_libraries = [0; 32];
+    affected_libraries[31] = 0x80;
+    let elup = |library_id| ELUp {
+        library_type: LibraryType::Cues,
+        library_id,
+        update_flags: ELUp::EXISTING_ELEMENTS_UPDATED,
+        affected_elements,
+        affected_libraries,
+    };
+    let nested = LibraryId {
+        level: 3,
+        level_1: 1,
+        level_2: 2,
+        level_3: 3,
+    };
+
+    let bytes = encode(Version::V1_2, elup(nested));
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 4 + 1 + 32 + 32);
+    match decode(Version::V1_2, &bytes) {
+        MsexMessage::ELUp(msg) => assert_eq!(msg.message, elup(nested)),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    // The affected element and sub-library sets are introduced in 1.2.
+    let without_sets = |library_id| ELUp {
+        affected_elements: [0; 32],
+        affected_libraries: [0; 32],
+        ..elup(library_id)
+    };
+    let bytes = encode(Version::V1_1, elup(nested));
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 4 + 1);
+    match decode(Version::V1_1, &bytes) {
+        MsexMessage::ELUp(msg) => assert_eq!(msg.message, without_sets(nested)),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+    let id = LibraryId::from_number(9);
+    let bytes = encode(Version::V1_0, elup(id));
+    assert_eq!(bytes.len(), HEADERS_SIZE + 1 + 1 + 1);
+    match decode(Version::V1_0, &bytes) {
+        MsexMessage::ELUp(msg) => assert_eq!(msg.message, without_sets(id)),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+fn eein(serial_number: u32) -> EEIn<'static> {
+    let effect = EffectInformation {
+        element_number: 1,
+        serial_number,
+        dmx_range_min: 2,
+        dmx_range_max: 3,
+        effect_name: "ab".parse().unwrap(),
+        effect_parameter_count: 2,
+        effect_parameter_names: Cow::Owned(vec!["x".parse().unwrap(), "y".parse().unwrap()]),
+    };
+    EEIn {
+        library_id: LibraryId::from_number(4),
+        effect_count: 1,
+        effects: Cow::Owned(vec![effect]),
+    }
+}
+
+#[test]
+fn eein_round_trips_with_each_layout() {
+    // Element number, DMX range, name and the parameter names with their count.
+    let effect_size = 1 + 2 + 6 + 1 + 4 + 4;
+    let layouts = [
+        (Version::V1_0, 1 + 1 + effect_size, 0),
+        (Version::V1_1, 4 + 1 + effect_size, 0),
+        (Version::V1_2, 4 + 2 + 4 + effect_size, 5),
+    ];
+    for (version, size, serial_number) in layouts {
+        let bytes = encode(version, eein(5));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        match decode(version, &bytes) {
+            MsexMessage::EEIn(msg) => assert_eq!(msg.message, eein(serial_number)),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+fn glei(serial_number: u32) -> GLEI<'static> {
+    let element = GenericInformation {
+        element_number: 1,
+        serial_number,
+        dmx_range_min: 2,
+        dmx_range_max: 3,
+        name: "ab".parse().unwrap(),
+        version_timestamp: 1_234_567_890,
+    };
+    GLEI {
+        library_type: LibraryType::Cues,
+        library_id: LibraryId::from_number(4),
+        element_count: 1,
+        elements: Cow::Owned(vec![element]),
+    }
+}
+
+#[test]
+fn glei_round_trips_from_1_1() {
+    // Element number, DMX range, name and timestamp.
+    let element_size = 1 + 2 + 6 + 8;
+    let layouts = [
+        (Version::V1_1, 1 + 4 + 1 + element_size, 0),
+        (Version::V1_2, 1 + 4 + 2 + 4 + element_size, 5),
+    ];
+    for (version, size, serial_number) in layouts {
+        let bytes = encode(version, glei(5));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        match decode(version, &bytes) {
+            MsexMessage::GLEI(msg) => assert_eq!(msg.message, glei(serial_number)),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+#[test]
+fn glei_is_refused_prior_to_1_1() {
+    let unsupported = |result| match result {
+        Err(Error::UnsupportedVersion { major: 1, minor: 0 }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    };
+    unsupported(glei(0).write_to_bytes_versioned(Version::V1_0, vec![]));
+
+    // A GLEI carrying the 1.1 layout under a 1.0 header.
+    let mut bytes = encode(Version::V1_1, glei(0));
+    bytes[21] = 0;
+    unsupported(Packet::decode(&bytes).map(|_| ()));
+}
+
+fn ethn(library_id: LibraryId) -> EThn<'static> {
+    EThn {
+        library_type: LibraryType::Media,
+        library_id,
+        element_number: 1,
+        thumbnail_format: ThumbnailFormat::Rgb8,
+        thumbnail_width: 1,
+        thumbnail_height: 2,
+        thumbnail_buffer_size: 6,
+        thumbnail_buffer: Cow::Owned(vec![1, 2, 3, 4, 5, 6]),
+    }
+}
+
+#[test]
+fn ethn_round_trips_with_each_layout() {
+    let nested = LibraryId {
+        level: 2,
+        level_1: 1,
+        level_2: 2,
+        level_3: 0,
+    };
+    // Library type, element number, format, dimensions, buffer size and buffer.
+    let size = 1 + 1 + 4 + 4 + 2 + 6;
+    let layouts = [
+        (Version::V1_0, LibraryId::from_number(3), 1 + size),
+        (Version::V1_1, nested, 4 + size),
+        (Version::V1_2, nested, 4 + size),
+    ];
+    for (version, library_id, size) in layouts {
+        let bytes = encode(version, ethn(library_id));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        match decode(version, &bytes) {
+            MsexMessage::EThn(msg) => assert_eq!(msg.message, ethn(library_id)),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+fn elth(library_id: LibraryId) -> ELTh<'static> {
+    ELTh {
+        library_type: LibraryType::Effects,
+        library_id,
+        thumbnail_format: ThumbnailFormat::Jpeg,
+        thumbnail_width: 1,
+        thumbnail_height: 2,
+        thumbnail_buffer_size: 3,
+        thumbnail_buffer: Cow::Owned(vec![1, 2, 3]),
+    }
+}
+
+#[test]
+fn elth_round_trips_with_each_layout() {
+    let nested = LibraryId {
+        level: 3,
+        level_1: 1,
+        level_2: 2,
+        level_3: 3,
+    };
+    // Library type, format, dimensions, buffer size and buffer.
+    let size = 1 + 4 + 4 + 2 + 3;
+    let layouts = [
+        (Version::V1_0, LibraryId::from_number(3), 1 + size),
+        (Version::V1_1, nested, 4 + size),
+        (Version::V1_2, nested, 4 + size),
+    ];
+    for (version, library_id, size) in layouts {
+        let bytes = encode(version, elth(library_id));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        match decode(version, &bytes) {
+            MsexMessage::ELTh(msg) => assert_eq!(msg.message, elth(library_id)),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+#[test]
+fn gelt_carries_library_numbers_prior_to_1_1() {
+    let gelt = |library_ids: Vec<LibraryId>| GELT {
+        thumbnail_format: ThumbnailFormat::Rgb8,
+        thumbnail_width: 64,
+        thumbnail_height: 48,
+        thumbnail_flags: 1,
+        library_type: LibraryType::Media,
+        library_count: library_ids.len() as u16,
+        library_ids: Cow::Owned(library_ids),
+    };
+    let ids = vec![LibraryId::from_number(2), LibraryId::from_number(5)];
+    // Format, dimensions, flags and library type precede the count.
+    let size = 4 + 4 + 1 + 1;
+
+    let bytes = encode(Version::V1_0, gelt(ids.clone()));
+    assert_eq!(bytes.len(), HEADERS_SIZE + size + 1 + 2);
+    assert_eq!(bytes[HEADERS_SIZE + size..], [2, 2, 5]);
+    match decode(Version::V1_0, &bytes) {
+        MsexMessage::GELT(msg) => assert_eq!(msg.message, gelt(ids.clone())),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    for (version, count_size) in [(Version::V1_1, 1), (Version::V1_2, 2)] {
+        let bytes = encode(version, gelt(ids.clone()));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size + count_size + 2 * 4);
+        match decode(version, &bytes) {
+            MsexMessage::GELT(msg) => assert_eq!(msg.message, gelt(ids.clone())),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+#[test]
+fn bgr_to_rgb_swaps_whole_triples() {
+    let mut pixels = [1, 2, 3, 4, 5, 6, 7, 8];
+    bgr_to_rgb(&mut pixels);
+    assert_eq!(pixels, [3, 2, 1, 6, 5, 4, 7, 8]);
+    // The conversion is its own inverse.
+    bgr_to_rgb(&mut pixels);
+    assert_eq!(pixels, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn vsrc_and_rqst_share_a_layout_across_versions() {
+    let source = SourceInformation {
+        source_identifier: 1,
+        source_name: "ab".parse().unwrap(),
+        physical_output: 0,
+        layer_number: 0xFF,
+        flags: 1,
+        width: 1920,
+        height: 1080,
+    };
+    let vsrc = VSrc {
+        source_count: 1,
+        sources: Cow::Owned(vec![source]),
+    };
+    let rqst = RqSt {
+        source_identifier: 1,
+        frame_format: StreamFormat::Jpeg,
+        frame_width: 64,
+        frame_height: 48,
+        fps: 25,
+        timeout: 5,
+    };
+    for version in [Version::V1_0, Version::V1_1, Version::V1_2] {
+        let bytes = encode(version, vsrc.clone());
+        // The count followed by the identifier, name, output, layer, flags and dimensions.
+        assert_eq!(bytes.len(), HEADERS_SIZE + 2 + 2 + 6 + 1 + 1 + 2 + 2 + 2);
+        match decode(version, &bytes) {
+            MsexMessage::VSrc(msg) => assert_eq!(msg.message, vsrc),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+
+        let bytes = encode(version, rqst);
+        assert_eq!(bytes.len(), HEADERS_SIZE + 2 + 4 + 2 + 2 + 1 + 1);
+        match decode(version, &bytes) {
+            MsexMessage::RqSt(msg) => assert_eq!(msg.message, rqst),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
+
+#[test]
+fn stfr_carries_the_uuid_from_1_2() {
+    let stfr = |media_server_uuid| StFr {
+        media_server_uuid,
+        source_identifier: 1,
+        frame_format: StreamFormat::Rgb8,
+        frame_width: 1,
+        frame_height: 1,
+        frame_buffer_size: 3,
+        frame_buffer: Cow::Owned(vec![1, 2, 3]),
+    };
+    let uuid = Uuid(*b"00000000-0000-0000-0000-000000000001");
+    // Source identifier, format, dimensions, buffer size and buffer.
+    let size = 2 + 4 + 4 + 2 + 3;
+
+    let bytes = encode(Version::V1_2, stfr(uuid));
+    assert_eq!(bytes.len(), HEADERS_SIZE + 36 + size);
+    match decode(Version::V1_2, &bytes) {
+        MsexMessage::StFr(msg) => assert_eq!(msg.message, stfr(uuid)),
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+    for version in [Version::V1_0, Version::V1_1] {
+        let bytes = encode(version, stfr(uuid));
+        assert_eq!(bytes.len(), HEADERS_SIZE + size);
+        assert_eq!(bytes[HEADERS_SIZE..HEADERS_SIZE + 2], 1u16.to_le_bytes());
+        match decode(version, &bytes) {
+            MsexMessage::StFr(msg) => assert_eq!(msg.message, stfr(Uuid::default())),
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use citp::protocol::{sdmx, Cookie, Packet, SdmxMessage};
+use citp::Error;
+use std::ffi::CString;
+
+fn unam_bytes() -> Vec<u8> {
+    let unam = sdmx::UNam {
+        universe_index: 1,
+        universe_name: CString::new("abc").unwrap(),
+    };
+    Packet::from(SdmxMessage::UNam(sdmx::Message::new(unam)))
+        .encode()
+        .unwrap()
+}
+
+#[test]
+fn trailing_bytes_are_ignored() {
+    let mut bytes = unam_bytes();
+    let packet = Packet::decode(&bytes).unwrap();
+    bytes.extend_from_slice(b"next");
+    assert_eq!(Packet::decode(&bytes).unwrap(), packet);
+}
+
+#[test]
+fn message_size_must_match_the_message() {
+    let mut bytes = unam_bytes();
+    let len = bytes.len();
+    bytes.push(0);
+    bytes[8..12].copy_from_slice(&(len as u32 + 1).to_le_bytes());
+    match Packet::decode(&bytes) {
+        Err(Error::MessageSizeMismatch {
+            message_size,
+            actual,
+        }) => {
+            assert_eq!(message_size, len + 1);
+            assert_eq!(actual, len);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn unknown_messages_are_preserved() {
+    let mut bytes = unam_bytes();
+    bytes[20..24].copy_from_slice(b"Abcd");
+    match Packet::decode(&bytes).unwrap() {
+        Packet::Unknown {
+            layer,
+            cookie,
+            payload,
+        } => {
+            assert_eq!(layer, Cookie::from(*b"SDMX"));
+            assert_eq!(cookie, Cookie::from(*b"Abcd"));
+            assert_eq!(payload, bytes);
+        }
+        packet => panic!("unexpected packet: {:?}", packet),
+    }
+}
+
+#[test]
+fn known_refuses_unknown_messages() {
+    let mut bytes = unam_bytes();
+    bytes[20..24].copy_from_slice(b"Abcd");
+    match Packet::decode(&bytes).unwrap().known() {
+        Err(Error::UnknownMessage { layer, cookie }) => {
+            assert_eq!(layer, Cookie::from(*b"SDMX"));
+            assert_eq!(cookie, Cookie::from(*b"Abcd"));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+    bytes[16..20].copy_from_slice(b"Abcd");
+    match Packet::decode(&bytes).unwrap().known() {
+        Err(Error::UnknownLayer { layer }) => assert_eq!(layer, Cookie::from(*b"Abcd")),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn citp_header_of_unknown_packets_is_read_from_the_payload() {
+    let mut bytes = unam_bytes();
+    bytes[20..24].copy_from_slice(b"Abcd");
+    let packet = Packet::decode(&bytes).unwrap();
+    assert_eq!(
+        packet.citp_header().unwrap().message_size as usize,
+        bytes.len()
+    );
+
+    // A caller-built packet need not begin with a valid base header.
+    let packet = Packet::Unknown {
+        layer: Cookie::from(*b"SDMX"),
+        cookie: Cookie::from(*b"Abcd"),
+        payload: b"CITP".to_vec(),
+    };
+    match packet.citp_header() {
+        Err(Error::Truncated { .. }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,143 @@
+use citp::net::request::{PendingRequests, Received};
+use citp::protocol::msex::{self, Nack, Version};
+use citp::protocol::{
+    sdmx, Header, Kind, MsexMessage, Packet, RequestCounter, RequestIndex, SdmxMessage,
+};
+use std::ffi::CString;
+use std::time::{Duration, Instant};
+
+const TIMEOUT: Duration = Duration::from_secs(1);
+
+fn response(kind: Kind) -> Packet {
+    let mut msg = sdmx::Message::new(sdmx::UNam {
+        universe_index: 1,
+        universe_name: CString::new("abc").unwrap(),
+    });
+    msg.sdmx_header.citp_header.kind = kind;
+    Packet::from(SdmxMessage::UNam(msg))
+}
+
+fn nack(kind: Kind) -> Packet {
+    let mut msg = msex::Message::new(
+        Version::V1_2,
+        Nack {
+            received_content_type: u32::from_le_bytes(*b"GELI"),
+        },
+    );
+    msg.msex_header.citp_header.kind = kind;
+    Packet::from(MsexMessage::Nack(msg))
+}
+
+fn index(index: u16) -> RequestIndex {
+    RequestIndex::new(index).unwrap()
+}
+
+#[test]
+fn request_counter_wraps_around_to_one() {
+    let mut counter = RequestCounter::new();
+    assert_eq!(counter.next_index(), RequestIndex::FIRST);
+    assert_eq!(counter.next_index().get(), 2);
+    for _ in 2..u16::MAX - 1 {
+        counter.next_index();
+    }
+    assert_eq!(counter.next_index().get(), u16::MAX);
+    // The `0` "ignored" value is skipped.
+    assert_eq!(counter.next_index(), RequestIndex::FIRST);
+}
+
+#[test]
+fn responses_complete_their_request() {
+    let now = Instant::now();
+    let mut pending = PendingRequests::new();
+    let mut header = Header::new(0, 0);
+    let first = pending.insert(&mut header, "a", TIMEOUT, now).unwrap();
+    assert_eq!(header.kind.request_index(), Some(first));
+    let second = pending.insert(&mut header, "b", TIMEOUT, now).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(pending.len(), 2);
+
+    let packet = response(Kind::response(second));
+    match pending.complete(packet.clone()) {
+        Received::Response {
+            index,
+            request,
+            packet: received,
+        } => {
+            assert_eq!(index, second);
+            assert_eq!(request, "b");
+            assert_eq!(received, packet);
+        }
+        received => panic!("unexpected result: {:?}", received),
+    }
+    assert_eq!(pending.get(first), Some(&"a"));
+    assert_eq!(pending.get(second), None);
+
+    // A second response to the same request, and messages without an index, are unsolicited.
+    let packet = response(Kind::response(second));
+    assert_eq!(
+        pending.complete(packet.clone()),
+        Received::Unsolicited(packet)
+    );
+    let packet = response(Kind::IGNORED);
+    assert_eq!(
+        pending.complete(packet.clone()),
+        Received::Unsolicited(packet)
+    );
+    assert_eq!(pending.len(), 1);
+}
+
+#[test]
+fn nacks_are_distinguished_from_responses() {
+    let now = Instant::now();
+    let mut pending = PendingRequests::new();
+    let mut header = Header::new(0, 0);
+    let requested = pending.insert(&mut header, "a", TIMEOUT, now).unwrap();
+    match pending.complete(nack(Kind::response(requested))) {
+        Received::Nack { index, request, .. } => {
+            assert_eq!(index, requested);
+            assert_eq!(request, "a");
+        }
+        received => panic!("unexpected result: {:?}", received),
+    }
+    assert!(pending.is_empty());
+}
+
+#[test]
+fn expired_requests_are_removed_in_index_order() {
+    let t0 = Instant::now();
+    let mut pending = PendingRequests::new();
+    let mut header = Header::new(0, 0);
+    pending.insert(&mut header, "a", TIMEOUT * 2, t0).unwrap();
+    pending.insert(&mut header, "b", TIMEOUT, t0).unwrap();
+    pending.insert(&mut header, "c", TIMEOUT, t0).unwrap();
+    assert_eq!(pending.next_deadline(), Some(t0 + TIMEOUT));
+
+    let just_before = t0 + TIMEOUT - Duration::from_nanos(1);
+    assert!(pending.remove_expired(just_before).is_empty());
+    assert_eq!(
+        pending.remove_expired(t0 + TIMEOUT),
+        vec![(index(2), "b"), (index(3), "c")]
+    );
+    assert_eq!(pending.next_deadline(), Some(t0 + TIMEOUT * 2));
+    assert_eq!(pending.cancel(index(1)), Some("a"));
+    assert_eq!(pending.next_deadline(), None);
+}
+
+#[test]
+fn pending_indices_are_skipped_and_a_full_table_is_refused() {
+    let now = Instant::now();
+    let mut pending = PendingRequests::new();
+    let mut header = Header::new(0, 0);
+    for i in 1..=u16::MAX {
+        assert_eq!(pending.insert(&mut header, i, TIMEOUT, now), Some(index(i)));
+    }
+    header.kind = Kind::IGNORED;
+    assert_eq!(pending.insert(&mut header, 0, TIMEOUT, now), None);
+    assert_eq!(header.kind.request_index(), None);
+    assert_eq!(pending.get(index(1)), Some(&1));
+
+    // Once an index is freed, the allocation skips over those still pending.
+    assert_eq!(pending.cancel(index(3)), Some(3));
+    assert_eq!(pending.insert(&mut header, 0, TIMEOUT, now), Some(index(3)));
+    assert_eq!(header.kind.request_index(), Some(index(3)));
+}
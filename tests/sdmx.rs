@@ -0,0 +1,285 @@
+use citp::protocol::sdmx::{
+    self, Buffer, Capa, Capabilities, CapabilityNegotiator, ChBk, ChLs, ChannelChange,
+    ChannelLevel, DmxState, Receiver, SXSr, Sxus, Transmitter,
+};
+use citp::protocol::{Packet, SdmxMessage, SizeBytes};
+use citp::Error;
+use std::borrow::Cow;
+use std::ffi::CString;
+use std::time::{Duration, Instant};
+
+fn chbk(blind: u8, universe_index: u8, first_channel: u16, levels: &[u8]) -> SdmxMessage {
+    SdmxMessage::ChBk(sdmx::Message::new(ChBk {
+        blind,
+        universe_index,
+        first_channel,
+        channel_levels: Cow::Owned(levels.to_vec()),
+    }))
+}
+
+fn chls(channel_levels: &[(u8, u16, u8)]) -> SdmxMessage {
+    let channel_levels = channel_levels
+        .iter()
+        .map(|&(universe_index, channel, channel_level)| ChannelLevel {
+            universe_index,
+            channel,
+            channel_level,
+        })
+        .collect();
+    SdmxMessage::ChLs(sdmx::Message::new(ChLs {
+        channel_levels: Cow::Owned(channel_levels),
+    }))
+}
+
+#[test]
+fn dmx_state_is_bounded_by_the_last_channel() {
+    let mut state = DmxState::new();
+    let changes: Vec<ChannelChange> = state
+        .update(&chbk(0, 0, 65_534, &[1, 2]))
+        .unwrap()
+        .collect();
+    assert_eq!(changes.len(), 2);
+    assert_eq!(changes[1].channel, 65_535);
+    assert_eq!(state.level(Buffer::Live, 0, 65_535), 2);
+
+    // A block extending beyond channel 65_535 is refused without applying any levels.
+    match state.update(&chbk(0, 0, 65_534, &[3, 4, 5, 6])) {
+        Err(Error::ChannelOutOfRange { channel }) => assert_eq!(channel, 65_537),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(state.level(Buffer::Live, 0, 65_534), 1);
+    assert_eq!(state.level(Buffer::Live, 0, 65_535), 2);
+
+    let changes: Vec<ChannelChange> = state.update(&chls(&[(0, 65_535, 5)])).unwrap().collect();
+    assert_eq!(changes[0].old, 2);
+    assert_eq!(state.levels(Buffer::Live, 0).unwrap().len(), 65_536);
+
+    assert_eq!(state.slots(Buffer::Live, 0, 127).unwrap()[511], 5);
+    assert_eq!(state.slots(Buffer::Live, 0, 128), None);
+    assert_eq!(state.slots(Buffer::Blind, 0, 0), None);
+}
+
+#[test]
+fn receiver_reverts_to_live_at_exactly_the_blind_timeout() {
+    let t0 = Instant::now();
+    let timeout = Duration::from_millis(500);
+    let mut receiver = Receiver::new(timeout);
+    receiver.update(&chbk(0, 1, 0, &[10]), t0).unwrap();
+    receiver.update(&chbk(1, 1, 0, &[20]), t0).unwrap();
+
+    let just_before = t0 + timeout - Duration::from_nanos(1);
+    assert_eq!(receiver.active_buffer(1, just_before), Buffer::Blind);
+    assert_eq!(receiver.active_levels(1, just_before).unwrap()[0], 20);
+    assert!(receiver.revert_expired(just_before).is_empty());
+
+    let deadline = t0 + timeout;
+    assert_eq!(receiver.active_buffer(1, deadline), Buffer::Live);
+    assert_eq!(receiver.active_levels(1, deadline).unwrap()[0], 10);
+    assert_eq!(receiver.revert_expired(deadline), vec![1]);
+    assert_eq!(receiver.state().levels(Buffer::Blind, 1), None);
+
+    // A blind block received later switches the universe back over to blind.
+    receiver.update(&chbk(1, 1, 0, &[30]), deadline).unwrap();
+    assert_eq!(receiver.active_levels(1, deadline).unwrap()[0], 30);
+}
+
+#[test]
+fn chls_channel_levels_are_four_bytes() {
+    let msg = chls(&[(0, 1, 2), (3, 4, 5)]);
+    let bytes = Packet::from(msg.clone()).encode().unwrap();
+    // Base header, SDMX header, the `u16` count and four bytes per channel level.
+    assert_eq!(bytes.len(), 20 + 4 + 2 + 2 * 4);
+    match msg {
+        SdmxMessage::ChLs(ref chls) => assert_eq!(chls.size_bytes(), bytes.len()),
+        _ => unreachable!(),
+    }
+    assert_eq!(Packet::decode(&bytes).unwrap(), Packet::from(msg));
+}
+
+#[test]
+fn transmitter_chooses_the_smaller_encoding() {
+    let t0 = Instant::now();
+    let mut levels = vec![0; 512];
+    let mut transmitter = Transmitter::new(Duration::from_secs(1));
+
+    // The first transmission sends all levels.
+    match &transmitter.transmit(0, &levels, t0).unwrap()[..] {
+        [SdmxMessage::ChBk(msg)] => {
+            assert_eq!(msg.message.first_channel, 0);
+            assert_eq!(msg.message.channel_levels.len(), 512);
+        }
+        msgs => panic!("unexpected messages: {:?}", msgs),
+    }
+    assert!(transmitter.transmit(0, &levels, t0).unwrap().is_empty());
+
+    // Scattered changes are sent as separate blocks until ChLs has been negotiated.
+    levels[0] = 1;
+    levels[500] = 2;
+    let msgs = transmitter.transmit(0, &levels, t0).unwrap();
+    assert_eq!(msgs.len(), 2);
+    assert!(msgs.iter().all(|msg| matches!(msg, SdmxMessage::ChBk(_))));
+
+    let mut capabilities = Capabilities::empty();
+    capabilities.insert(Capa::CHANNEL_LIST);
+    transmitter.set_capabilities(capabilities);
+    levels[0] = 3;
+    levels[500] = 4;
+    match &transmitter.transmit(0, &levels, t0).unwrap()[..] {
+        [SdmxMessage::ChLs(msg)] => {
+            let channels: Vec<(u16, u8)> = msg
+                .message
+                .channel_levels
+                .iter()
+                .map(|ch| (ch.channel, ch.channel_level))
+                .collect();
+            assert_eq!(channels, vec![(0, 3), (500, 4)]);
+        }
+        msgs => panic!("unexpected messages: {:?}", msgs),
+    }
+
+    // A run of consecutive changes is cheaper as a single block.
+    for level in &mut levels[100..200] {
+        *level = 5;
+    }
+    match &transmitter.transmit(0, &levels, t0).unwrap()[..] {
+        [SdmxMessage::ChBk(msg)] => {
+            assert_eq!(msg.message.first_channel, 100);
+            assert_eq!(msg.message.channel_levels.len(), 100);
+        }
+        msgs => panic!("unexpected messages: {:?}", msgs),
+    }
+}
+
+#[test]
+fn transmitter_refreshes_all_levels() {
+    let t0 = Instant::now();
+    let interval = Duration::from_secs(1);
+    let levels = vec![7; 16];
+    let mut transmitter = Transmitter::new(interval);
+    assert_eq!(transmitter.transmit(0, &levels, t0).unwrap().len(), 1);
+    let just_before = t0 + interval - Duration::from_nanos(1);
+    assert!(transmitter
+        .transmit(0, &levels, just_before)
+        .unwrap()
+        .is_empty());
+    match &transmitter.transmit(0, &levels, t0 + interval).unwrap()[..] {
+        [SdmxMessage::ChBk(msg)] => assert_eq!(msg.message.channel_levels[..], levels[..]),
+        msgs => panic!("unexpected messages: {:?}", msgs),
+    }
+    // Other universes are refreshed independently.
+    assert_eq!(
+        transmitter
+            .transmit(1, &levels, t0 + interval)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn transmitter_splits_levels_into_blocks_that_fit_a_datagram() {
+    let t0 = Instant::now();
+    let levels = vec![9; DmxState::CHANNEL_COUNT];
+    let mut transmitter = Transmitter::new(Duration::from_secs(1));
+    assert_eq!(
+        transmitter.max_block_size(),
+        Transmitter::DEFAULT_MAX_BLOCK_SIZE
+    );
+    let msgs = transmitter.transmit(0, &levels, t0).unwrap();
+    assert_eq!(msgs.len(), DmxState::CHANNEL_COUNT / 512);
+    for (i, msg) in msgs.iter().enumerate() {
+        match msg {
+            SdmxMessage::ChBk(msg) => {
+                assert_eq!(msg.message.first_channel as usize, i * 512);
+                assert_eq!(msg.message.channel_levels.len(), 512);
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+
+    let mut transmitter = Transmitter::new(Duration::from_secs(1)).with_max_block_size(100);
+    let msgs = transmitter.transmit(0, &levels[..250], t0).unwrap();
+    let blocks: Vec<(u16, usize)> = msgs
+        .iter()
+        .map(|msg| match msg {
+            SdmxMessage::ChBk(msg) => (msg.message.first_channel, msg.message.channel_levels.len()),
+            msg => panic!("unexpected message: {:?}", msg),
+        })
+        .collect();
+    assert_eq!(blocks, vec![(0, 100), (100, 100), (200, 50)]);
+}
+
+#[test]
+fn transmitter_refuses_more_levels_than_channels() {
+    let levels = vec![0; DmxState::CHANNEL_COUNT + 4];
+    let mut transmitter = Transmitter::new(Duration::from_secs(1));
+    match transmitter.transmit(0, &levels, Instant::now()) {
+        Err(Error::ChannelOutOfRange { channel }) => assert_eq!(channel, 65_539),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn ma_net_capability_is_104() {
+    assert_eq!(Capa::MA_NET_EXTERNAL_SOURCES, 104);
+    let capa = Capa {
+        capabilities: Cow::Owned(vec![104, 999]),
+    };
+    let capabilities = Capabilities::from(&capa);
+    assert!(capabilities.contains(Capa::MA_NET_EXTERNAL_SOURCES));
+    assert_eq!(capabilities.iter().collect::<Vec<_>>(), vec![104]);
+}
+
+#[test]
+fn negotiator_refuses_messages_requiring_unnegotiated_capabilities() {
+    let sxsr = |connection_string: &str| {
+        SdmxMessage::SXSr(sdmx::Message::new(SXSr {
+            connection_string: CString::new(connection_string).unwrap(),
+        }))
+    };
+    let sxus = SdmxMessage::Sxus(sdmx::Message::new(Sxus {
+        universe_index: 0,
+        connection_string: CString::new("BSRE1.31/1/1").unwrap(),
+    }));
+    let chls = chls(&[(0, 0, 255)]);
+    let refused = |negotiator: &CapabilityNegotiator, msg: &SdmxMessage| match negotiator.check(msg)
+    {
+        Err(Error::CapabilityNotNegotiated { capability }) => Some(capability),
+        Err(err) => panic!("unexpected error: {}", err),
+        Ok(()) => None,
+    };
+
+    // Nothing may be used until the remote peer's Capa has been received.
+    let mut negotiator = CapabilityNegotiator::new(Capabilities::all());
+    assert_eq!(refused(&negotiator, &chls), Some(Capa::CHANNEL_LIST));
+    assert_eq!(
+        refused(&negotiator, &sxsr("ArtNet/0/0/1")),
+        Some(Capa::EXTERNAL_SOURCE)
+    );
+    assert_eq!(
+        refused(&negotiator, &sxus),
+        Some(Capa::PER_UNIVERSE_EXTERNAL_SOURCES)
+    );
+    assert_eq!(refused(&negotiator, &chbk(0, 0, 0, &[1])), None);
+
+    let capa = Capa {
+        capabilities: Cow::Owned(vec![
+            Capa::CHANNEL_LIST,
+            Capa::EXTERNAL_SOURCE,
+            Capa::PER_UNIVERSE_EXTERNAL_SOURCES,
+            Capa::ART_NET_EXTERNAL_SOURCES,
+        ]),
+    };
+    negotiator.receive_capa(&capa);
+    assert_eq!(refused(&negotiator, &chls), None);
+    assert_eq!(refused(&negotiator, &sxsr("ArtNet/0/0/1")), None);
+    // The source protocol requires its own capability.
+    assert_eq!(
+        refused(&negotiator, &sxsr("MANet/2/0/1")),
+        Some(Capa::MA_NET_EXTERNAL_SOURCES)
+    );
+    assert_eq!(
+        refused(&negotiator, &sxus),
+        Some(Capa::BSR_E131_EXTERNAL_SOURCES)
+    );
+}
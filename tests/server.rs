@@ -0,0 +1,187 @@
+use citp::net::tcp::Session;
+use citp::protocol::msex::server::MockMediaServer;
+use citp::protocol::msex::{
+    self, CInf, GEIn, GETh, LibraryId, LibraryType, SizeBytesVersioned, ThumbnailFormat, Version,
+    WriteToBytesVersioned, GELI,
+};
+use citp::protocol::{ContentType, MsexMessage, Packet, WriteBytes};
+use citp::Error;
+use std::borrow::Cow;
+use std::io::Write;
+use std::time::Duration;
+
+/// Connect to a mock server running on its own thread.
+fn connect() -> (Session, std::thread::JoinHandle<citp::Result<()>>) {
+    let server = MockMediaServer::bind().unwrap();
+    let addr = server.local_addr().unwrap();
+    let handle = server.spawn();
+    let session = Session::connect(addr).unwrap();
+    session
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    (session, handle)
+}
+
+fn send<T>(session: &mut Session, message: T)
+where
+    T: ContentType + SizeBytesVersioned + WriteToBytesVersioned,
+{
+    session
+        .send(&msex::Message::new(Version::V1_2, message))
+        .unwrap();
+}
+
+/// Receive the next MSEX message, skipping the LSta messages sent periodically by the server.
+fn recv(session: &mut Session) -> MsexMessage {
+    loop {
+        match session.recv().unwrap() {
+            Packet::Msex(MsexMessage::LSta(_)) => continue,
+            Packet::Msex(msg) => return msg,
+            packet => panic!("unexpected packet: {:?}", packet),
+        }
+    }
+}
+
+fn handshake(session: &mut Session) {
+    send(session, CInf::new(&[Version::V1_0, Version::V1_2]));
+    match recv(session) {
+        MsexMessage::SInf(msg) => {
+            assert_eq!(msg.msex_header.version(), Version::V1_2);
+            assert_eq!(msg.message.uuid.0, *MockMediaServer::DEFAULT_UUID);
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+}
+
+fn gein(library_id: LibraryId) -> GEIn<'static> {
+    GEIn {
+        library_type: LibraryType::Media,
+        library_id,
+        element_count: 0,
+        element_numbers: Cow::Owned(vec![]),
+    }
+}
+
+#[test]
+fn handshake_and_library_requests() {
+    let (mut session, handle) = connect();
+    handshake(&mut session);
+
+    let geli = GELI {
+        library_type: LibraryType::Media,
+        library_parent_id: LibraryId::ROOT,
+        library_count: 0,
+        library_numbers: Cow::Owned(vec![]),
+    };
+    send(&mut session, geli);
+    let library_id = match recv(&mut session) {
+        MsexMessage::ELIn(msg) => {
+            assert_eq!(msg.message.library_type, LibraryType::Media);
+            assert_eq!(msg.message.libraries.len(), 1);
+            let library = &msg.message.libraries[0];
+            assert_eq!(library.element_count, 3);
+            library.id
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    };
+
+    send(&mut session, gein(library_id));
+    match recv(&mut session) {
+        MsexMessage::MEIn(msg) => {
+            assert_eq!(msg.message.library_id, library_id);
+            let numbers: Vec<u8> = msg.message.elements.iter().map(|m| m.number).collect();
+            assert_eq!(numbers, vec![1, 2, 3]);
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    drop(session);
+    handle.join().unwrap().unwrap();
+}
+
+#[test]
+fn unknown_libraries_are_refused() {
+    let (mut session, handle) = connect();
+    handshake(&mut session);
+    let unknown = LibraryId::from_number(42);
+
+    send(&mut session, gein(unknown));
+    match recv(&mut session) {
+        MsexMessage::Nack(msg) => {
+            assert_eq!(msg.message.received_content_type, GEIn::COOKIE.get());
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    let geth = GETh {
+        thumbnail_format: ThumbnailFormat::Rgb8,
+        thumbnail_width: 0,
+        thumbnail_height: 0,
+        thumbnail_flags: 0,
+        library_type: LibraryType::Media,
+        library_id: unknown,
+        element_count: 0,
+        element_numbers: Cow::Owned(vec![]),
+    };
+    send(&mut session, geth);
+    match recv(&mut session) {
+        MsexMessage::Nack(msg) => {
+            assert_eq!(msg.message.received_content_type, GETh::COOKIE.get());
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    drop(session);
+    handle.join().unwrap().unwrap();
+}
+
+#[test]
+fn undecodable_messages_are_refused() {
+    let (mut session, handle) = connect();
+    handshake(&mut session);
+
+    // A GETh whose thumbnail format is not a known cookie.
+    let geth = GETh {
+        thumbnail_format: ThumbnailFormat::Rgb8,
+        thumbnail_width: 0,
+        thumbnail_height: 0,
+        thumbnail_flags: 0,
+        library_type: LibraryType::Media,
+        library_id: LibraryId::from_number(1),
+        element_count: 0,
+        element_numbers: Cow::Owned(vec![]),
+    };
+    let mut bytes = vec![];
+    bytes
+        .write_bytes(msex::Message::new(Version::V1_2, geth))
+        .unwrap();
+    bytes[26..30].copy_from_slice(b"XXXX");
+    assert!(Packet::decode(&bytes).is_err());
+    session.stream().write_all(&bytes).unwrap();
+    match recv(&mut session) {
+        MsexMessage::Nack(msg) => {
+            assert_eq!(msg.message.received_content_type, GETh::COOKIE.get());
+        }
+        msg => panic!("unexpected message: {:?}", msg),
+    }
+
+    // The session continues.
+    send(&mut session, gein(LibraryId::from_number(1)));
+    assert!(matches!(recv(&mut session), MsexMessage::MEIn(_)));
+
+    drop(session);
+    handle.join().unwrap().unwrap();
+}
+
+#[test]
+fn invalid_base_header_ends_the_session() {
+    let (session, handle) = connect();
+    let mut header = [0; 20];
+    header[..4].copy_from_slice(b"XXXX");
+    header[8..12].copy_from_slice(&20u32.to_le_bytes());
+    session.stream().write_all(&header).unwrap();
+    match handle.join().unwrap() {
+        Err(Error::BadCookie { .. }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
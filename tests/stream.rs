@@ -0,0 +1,111 @@
+use citp::protocol::msex::stream::FrameAssembler;
+use citp::protocol::msex::{FragmentHeader, StFr, StreamFormat, Uuid};
+use citp::protocol::WriteBytes;
+use citp::Error;
+use std::borrow::Cow;
+
+/// A fragment of frame `frame_index`, carrying `data` at the given byte offset.
+fn fragment(
+    frame_index: u32,
+    fragment_count: u16,
+    fragment_index: u16,
+    fragment_byte_offset: u32,
+    data: &[u8],
+) -> StFr<'static> {
+    let header = FragmentHeader {
+        frame_index,
+        fragment_count,
+        fragment_index,
+        fragment_byte_offset,
+    };
+    let mut frame_buffer = vec![];
+    frame_buffer.write_bytes(header).unwrap();
+    frame_buffer.extend_from_slice(data);
+    StFr {
+        media_server_uuid: Uuid([b'0'; 36]),
+        source_identifier: 1,
+        frame_format: StreamFormat::FragmentedJpeg,
+        frame_width: 64,
+        frame_height: 48,
+        frame_buffer_size: frame_buffer.len() as u16,
+        frame_buffer: Cow::Owned(frame_buffer),
+    }
+}
+
+#[test]
+fn out_of_order_fragments_are_reassembled() {
+    let mut assembler = FrameAssembler::new();
+    assert_eq!(assembler.push(&fragment(7, 3, 2, 4, b"ef")).unwrap(), None);
+    assert_eq!(assembler.push(&fragment(7, 3, 0, 0, b"ab")).unwrap(), None);
+    let frame = assembler
+        .push(&fragment(7, 3, 1, 2, b"cd"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.frame_index, Some(7));
+    assert_eq!(frame.format, StreamFormat::Jpeg);
+    assert_eq!((frame.width, frame.height), (64, 48));
+    assert_eq!(frame.data, b"abcdef");
+}
+
+#[test]
+fn duplicate_fragments_are_ignored() {
+    let mut assembler = FrameAssembler::new();
+    assert_eq!(assembler.push(&fragment(1, 2, 0, 0, b"ab")).unwrap(), None);
+    assert_eq!(assembler.push(&fragment(1, 2, 0, 0, b"xx")).unwrap(), None);
+    let frame = assembler
+        .push(&fragment(1, 2, 1, 2, b"cd"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.data, b"abcd");
+    // A late duplicate of the completed frame does not yield it again.
+    assert_eq!(assembler.push(&fragment(1, 2, 1, 2, b"cd")).unwrap(), None);
+}
+
+#[test]
+fn stale_fragments_are_ignored() {
+    let mut assembler = FrameAssembler::new();
+    assert_eq!(assembler.push(&fragment(5, 2, 0, 0, b"ab")).unwrap(), None);
+    // A fragment of a newer frame drops the partially received frame 5.
+    assert_eq!(assembler.push(&fragment(6, 2, 1, 2, b"gh")).unwrap(), None);
+    assert_eq!(assembler.push(&fragment(5, 2, 1, 2, b"cd")).unwrap(), None);
+    let frame = assembler
+        .push(&fragment(6, 2, 0, 0, b"ef"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(frame.frame_index, Some(6));
+    assert_eq!(frame.data, b"efgh");
+}
+
+#[test]
+fn invalid_fragments_are_rejected() {
+    let mut assembler = FrameAssembler::new();
+    match assembler.push(&fragment(1, 2, 2, 0, b"ab")) {
+        Err(Error::InvalidFragment {
+            frame_index: 1,
+            fragment_index: 2,
+            fragment_count: 2,
+        }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    assert_eq!(assembler.push(&fragment(1, 2, 0, 0, b"ab")).unwrap(), None);
+    match assembler.push(&fragment(1, 3, 1, 2, b"cd")) {
+        Err(Error::InvalidFragment {
+            fragment_count: 3, ..
+        }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn fragments_beyond_the_max_frame_size_are_rejected() {
+    let mut assembler = FrameAssembler::new().with_max_frame_size(4);
+    assert_eq!(assembler.push(&fragment(1, 2, 1, 2, b"cd")).unwrap(), None);
+    match assembler.push(&fragment(1, 2, 0, 3, b"ab")) {
+        Err(Error::FrameTooLarge { size: 5, max: 4 }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+    match FrameAssembler::new().push(&fragment(1, 1, 0, u32::MAX, b"ab")) {
+        Err(Error::FrameTooLarge { .. }) => (),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,116 @@
+use citp::net::tcp::Session;
+use citp::protocol::{pinf, Packet, PinfMessage};
+use citp::Error;
+use std::ffi::CString;
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+/// A connected pair of sessions over the loopback interface.
+fn loopback() -> (TcpStream, Session) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = Session::connect(addr).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    client
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    (server, client)
+}
+
+fn pnam(name: &str) -> Packet {
+    let pnam = pinf::PNam {
+        name: CString::new(name).unwrap(),
+    };
+    Packet::from(PinfMessage::PNam(pinf::Message::new(pnam)))
+}
+
+#[test]
+fn round_trip() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut client = Session::connect(addr).unwrap();
+    let mut server = Session::new(listener.accept().unwrap().0).unwrap();
+    client.send(&pnam("client")).unwrap();
+    assert_eq!(server.recv().unwrap(), pnam("client"));
+    server.send(&pnam("server")).unwrap();
+    assert_eq!(client.recv().unwrap(), pnam("server"));
+}
+
+#[test]
+fn several_messages_per_segment() {
+    let (mut stream, mut session) = loopback();
+    let mut bytes = vec![];
+    for name in ["a", "bb", "ccc"] {
+        bytes.extend(pnam(name).encode().unwrap());
+    }
+    stream.write_all(&bytes).unwrap();
+    for name in ["a", "bb", "ccc"] {
+        assert_eq!(session.recv().unwrap(), pnam(name));
+    }
+}
+
+#[test]
+fn message_split_across_segments() {
+    let (mut stream, mut session) = loopback();
+    stream.set_nodelay(true).unwrap();
+    let bytes = [
+        pnam("first").encode().unwrap(),
+        pnam("second").encode().unwrap(),
+    ]
+    .concat();
+    let writer = thread::spawn(move || {
+        // Split within the first base header, within the first message and across both messages.
+        for chunk in [&bytes[..7], &bytes[7..25], &bytes[25..40], &bytes[40..]] {
+            stream.write_all(chunk).unwrap();
+            stream.flush().unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+        stream
+    });
+    assert_eq!(session.recv().unwrap(), pnam("first"));
+    assert_eq!(session.recv().unwrap(), pnam("second"));
+    writer.join().unwrap();
+}
+
+#[test]
+fn oversized_message_is_refused() {
+    let (mut stream, session) = loopback();
+    let mut session = session.with_max_message_size(24);
+    stream
+        .write_all(&pnam("too long").encode().unwrap())
+        .unwrap();
+    match session.recv() {
+        Err(Error::MessageTooLarge { message_size, max }) => {
+            assert!(message_size > 24);
+            assert_eq!(max, 24);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn invalid_header_is_sticky() {
+    let (mut stream, mut session) = loopback();
+    let mut bytes = pnam("bad").encode().unwrap();
+    bytes[..4].copy_from_slice(b"XXXX");
+    bytes.extend(pnam("good").encode().unwrap());
+    stream.write_all(&bytes).unwrap();
+    for _ in 0..2 {
+        match session.recv() {
+            Err(Error::BadCookie { .. }) => (),
+            result => panic!("unexpected result: {:?}", result),
+        }
+    }
+}
+
+#[test]
+fn closed_connection() {
+    let (stream, mut session) = loopback();
+    drop(stream);
+    match session.recv() {
+        Err(Error::Io(err)) => assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
@@ -0,0 +1,66 @@
+use citp::protocol::{sdmx, Header, Packet, ReadBytes, SdmxMessage};
+use citp::Error;
+use std::ffi::CString;
+
+fn unam_bytes() -> Vec<u8> {
+    let unam = sdmx::UNam {
+        universe_index: 1,
+        universe_name: CString::new("abc").unwrap(),
+    };
+    Packet::from(SdmxMessage::UNam(sdmx::Message::new(unam)))
+        .encode()
+        .unwrap()
+}
+
+#[test]
+fn short_base_header_is_truncated() {
+    let bytes = unam_bytes();
+    match (&bytes[..10]).read_bytes::<Header>() {
+        Err(Error::Truncated { expected, actual }) => {
+            assert_eq!(actual, 10);
+            assert_eq!(expected, 12);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn message_shorter_than_message_size_is_truncated() {
+    let bytes = unam_bytes();
+    match Packet::decode(&bytes[..bytes.len() - 1]) {
+        Err(Error::Truncated { expected, actual }) => {
+            assert_eq!(expected, bytes.len());
+            assert_eq!(actual, bytes.len() - 1);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn unterminated_field_is_truncated() {
+    // Drop the nul terminator of the universe name and shrink `message_size` to match.
+    let mut bytes = unam_bytes();
+    bytes.pop();
+    let message_size = bytes.len() as u32;
+    bytes[8..12].copy_from_slice(&message_size.to_le_bytes());
+    match Packet::decode(&bytes) {
+        Err(Error::Truncated { expected, actual }) => assert_eq!(expected - actual, 1),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn count_overflow() {
+    let capa = sdmx::Capa {
+        capabilities: vec![sdmx::Capa::CHANNEL_LIST; u16::MAX as usize + 1].into(),
+    };
+    let packet = Packet::from(SdmxMessage::Capa(sdmx::Message::new(capa)));
+    match packet.encode() {
+        Err(Error::CountOverflow { field, count, max }) => {
+            assert_eq!(field, "capability_count");
+            assert_eq!(count, u16::MAX as usize + 1);
+            assert_eq!(max, u16::MAX as usize);
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}